@@ -0,0 +1,133 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Content-addressed file store: each file lives at <root>/<first two hex chars>/<sha256>
+pub struct AttachmentStore {
+    root: PathBuf,
+}
+
+pub struct StoredFile {
+    pub hash: String,
+    pub size: i64,
+}
+
+impl AttachmentStore {
+    pub fn new(root: PathBuf) -> Self {
+        AttachmentStore { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path_for(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[..2]).join(hash)
+    }
+
+    // Copies the file into the store; identical content is only kept once
+    pub fn store(&self, source: &Path) -> io::Result<StoredFile> {
+        let mut file = fs::File::open(source)?;
+        let mut hasher = Sha256::new();
+        let size = io::copy(&mut file, &mut hasher)? as i64;
+        let hash = format!("{:x}", hasher.finalize());
+
+        let target = self.path_for(&hash);
+        if !target.exists() {
+            fs::create_dir_all(target.parent().unwrap())?;
+            // Copy under a temporary name so an interrupted copy never looks complete
+            let partial = target.with_extension("partial");
+            fs::copy(source, &partial)?;
+            fs::rename(&partial, &target)?;
+        }
+        Ok(StoredFile { hash, size })
+    }
+
+    pub fn remove(&self, hash: &str) -> io::Result<()> {
+        match fs::remove_file(self.path_for(hash)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    // Deletes stored files whose hash is not in `referenced`; returns how many were removed
+    pub fn remove_unreferenced(&self, referenced: &HashSet<String>) -> io::Result<usize> {
+        let mut removed = 0;
+        let buckets = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        for bucket in buckets {
+            let bucket = bucket?.path();
+            if !bucket.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&bucket)? {
+                let path = entry?.path();
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+                if !referenced.contains(name) {
+                    fs::remove_file(&path)?;
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    // A copy with the original file name, so the OS picks the right application to open it
+    pub fn export_for_opening(&self, hash: &str, name: &str) -> io::Result<PathBuf> {
+        let dir = std::env::temp_dir().join("todo-wbs-app").join(hash);
+        fs::create_dir_all(&dir)?;
+        let file_name = Path::new(name).file_name().map(|n| n.to_os_string()).unwrap_or_else(|| hash.into());
+        let target = dir.join(file_name);
+        fs::copy(self.path_for(hash), &target)?;
+        Ok(target)
+    }
+}
+
+pub fn copy_dir_all(source: &Path, target: &Path) -> io::Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir_all(&path, &target.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, target.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+// MIME type from the file extension, for the common kinds of files attached to tasks
+pub fn mime_type(name: &str) -> &'static str {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "html" | "htm" => "text/html",
+        "zip" => "application/zip",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,125 @@
+use crate::calendar::WorkCalendar;
+use crate::db::{parse_date, Baseline, BaselineTask, Task};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VarianceKind {
+    // Present in both the baseline and the current plan
+    Planned,
+    // Created after the baseline was taken
+    Added,
+    // Deleted since the baseline was taken
+    Removed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskVariance {
+    pub task_id: String,
+    pub title: String,
+    pub kind: VarianceKind,
+    pub baseline_start: Option<String>,
+    pub baseline_end: Option<String>,
+    pub current_start: Option<String>,
+    pub current_end: Option<String>,
+    // Working days late (positive) or early (negative)
+    pub start_slip_days: Option<i64>,
+    pub finish_slip_days: Option<i64>,
+    pub baseline_progress: Option<i32>,
+    pub current_progress: Option<i32>,
+    pub progress_variance: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BaselineComparison {
+    pub baseline: Baseline,
+    // Slip of the latest finish date across the project
+    pub project_finish_slip_days: Option<i64>,
+    pub slipped_tasks: usize,
+    pub tasks: Vec<TaskVariance>,
+}
+
+fn slip(calendar: &WorkCalendar, baseline: &Option<String>, current: &Option<String>) -> Option<i64> {
+    Some(calendar.working_day_offset(parse_date(baseline)?, parse_date(current)?))
+}
+
+fn latest_end<'a>(dates: impl Iterator<Item = &'a Option<String>>) -> Option<String> {
+    dates.flatten().max().cloned()
+}
+
+// Per-task slippage and progress variance of the current plan against a baseline
+pub fn compare(
+    baseline: Baseline,
+    snapshot: &[BaselineTask],
+    tasks: &[Task],
+    calendar: &WorkCalendar,
+) -> BaselineComparison {
+    let current: HashMap<&str, &Task> = tasks.iter().map(|t| (t.id.as_str(), t)).collect();
+    let mut rows = Vec::new();
+
+    for planned in snapshot {
+        let row = match current.get(planned.task_id.as_str()) {
+            Some(task) => TaskVariance {
+                task_id: task.id.clone(),
+                title: task.title.clone(),
+                kind: VarianceKind::Planned,
+                start_slip_days: slip(calendar, &planned.start_date, &task.start_date),
+                finish_slip_days: slip(calendar, &planned.end_date, &task.end_date),
+                baseline_start: planned.start_date.clone(),
+                baseline_end: planned.end_date.clone(),
+                current_start: task.start_date.clone(),
+                current_end: task.end_date.clone(),
+                baseline_progress: Some(planned.progress),
+                current_progress: Some(task.progress),
+                progress_variance: Some(task.progress - planned.progress),
+            },
+            None => TaskVariance {
+                task_id: planned.task_id.clone(),
+                title: planned.title.clone(),
+                kind: VarianceKind::Removed,
+                baseline_start: planned.start_date.clone(),
+                baseline_end: planned.end_date.clone(),
+                current_start: None,
+                current_end: None,
+                start_slip_days: None,
+                finish_slip_days: None,
+                baseline_progress: Some(planned.progress),
+                current_progress: None,
+                progress_variance: None,
+            },
+        };
+        rows.push(row);
+    }
+
+    let snapshot_ids: Vec<&str> = snapshot.iter().map(|t| t.task_id.as_str()).collect();
+    for task in tasks.iter().filter(|t| !snapshot_ids.contains(&t.id.as_str())) {
+        rows.push(TaskVariance {
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            kind: VarianceKind::Added,
+            baseline_start: None,
+            baseline_end: None,
+            current_start: task.start_date.clone(),
+            current_end: task.end_date.clone(),
+            start_slip_days: None,
+            finish_slip_days: None,
+            baseline_progress: None,
+            current_progress: Some(task.progress),
+            progress_variance: None,
+        });
+    }
+
+    let project_finish_slip_days = slip(
+        calendar,
+        &latest_end(snapshot.iter().map(|t| &t.end_date)),
+        &latest_end(tasks.iter().map(|t| &t.end_date)),
+    );
+
+    BaselineComparison {
+        baseline,
+        project_finish_slip_days,
+        slipped_tasks: rows.iter().filter(|r| r.finish_slip_days.is_some_and(|d| d > 0)).count(),
+        tasks: rows,
+    }
+}
@@ -0,0 +1,377 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+pub const DEFAULT_CALENDAR_ID: &str = "default";
+
+// Years covered by the built-in Japanese holiday rules
+pub const JP_HOLIDAY_YEARS: std::ops::RangeInclusive<i32> = 2020..=2099;
+
+// Upper bound on working-day shifts and on the days scanned for the next working day, so a
+// bad input or a calendar without working days cannot stall while the database is locked
+pub const MAX_SCAN_DAYS: i64 = 3660;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Calendar {
+    pub id: String,
+    pub name: String,
+    pub work_week: Vec<Weekday>,
+    // Japanese public holidays are computed rather than stored
+    pub use_jp_holidays: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Holiday {
+    pub date: String,
+    pub name: String,
+}
+
+pub fn default_work_week() -> Vec<Weekday> {
+    vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]
+}
+
+// Working-day arithmetic for one calendar
+pub struct WorkCalendar {
+    work_week: Vec<Weekday>,
+    holidays: HashSet<NaiveDate>,
+    use_jp_holidays: bool,
+    jp_cache: std::cell::RefCell<HashMap<i32, HashSet<NaiveDate>>>,
+}
+
+impl WorkCalendar {
+    pub fn new(calendar: &Calendar, holidays: &[Holiday]) -> Self {
+        WorkCalendar {
+            work_week: calendar.work_week.clone(),
+            holidays: holidays
+                .iter()
+                .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok())
+                .collect(),
+            use_jp_holidays: calendar.use_jp_holidays,
+            jp_cache: Default::default(),
+        }
+    }
+
+    // Monday-to-Friday with Japanese holidays, used when no calendar has been loaded
+    pub fn standard() -> Self {
+        WorkCalendar {
+            work_week: default_work_week(),
+            holidays: HashSet::new(),
+            use_jp_holidays: true,
+            jp_cache: Default::default(),
+        }
+    }
+
+    fn is_jp_holiday(&self, date: NaiveDate) -> bool {
+        let mut cache = self.jp_cache.borrow_mut();
+        cache
+            .entry(date.year())
+            .or_insert_with(|| jp_holidays(date.year()).into_iter().map(|(d, _)| d).collect())
+            .contains(&date)
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.work_week.contains(&date.weekday())
+            && !self.holidays.contains(&date)
+            && !(self.use_jp_holidays && self.is_jp_holiday(date))
+    }
+
+    // Working days in the inclusive range
+    pub fn working_days_between(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        start
+            .iter_days()
+            .take_while(|d| *d <= end)
+            .filter(|d| self.is_working_day(*d))
+            .count() as i64
+    }
+
+    // Signed number of working days from `from` to `to`, negative when `to` is earlier
+    pub fn working_day_offset(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        if to >= from {
+            from.succ_opt().map_or(0, |from| self.working_days_between(from, to))
+        } else {
+            -to.succ_opt().map_or(0, |to| self.working_days_between(to, from))
+        }
+    }
+
+    // First working day on or after `date`; `date` itself when none is found within MAX_SCAN_DAYS
+    pub fn next_working_day(&self, date: NaiveDate) -> NaiveDate {
+        std::iter::successors(Some(date), |d| d.succ_opt())
+            .take(MAX_SCAN_DAYS as usize)
+            .find(|d| self.is_working_day(*d))
+            .unwrap_or(date)
+    }
+
+    // Last working day on or before `date`; `date` itself when none is found within MAX_SCAN_DAYS
+    pub fn previous_working_day(&self, date: NaiveDate) -> NaiveDate {
+        std::iter::successors(Some(date), |d| d.pred_opt())
+            .take(MAX_SCAN_DAYS as usize)
+            .find(|d| self.is_working_day(*d))
+            .unwrap_or(date)
+    }
+
+    // Dates for a task bar dragged from `old` to `new`. A move keeps the task's number of
+    // working days; a resize only snaps both ends onto working days. None when out of range.
+    pub fn reschedule(&self, old: (NaiveDate, NaiveDate), new: (NaiveDate, NaiveDate)) -> Option<(NaiveDate, NaiveDate)> {
+        let start = self.next_working_day(new.0);
+        if new.1 - new.0 == old.1 - old.0 {
+            let duration = self.working_days_between(old.0, old.1).max(1);
+            Some((start, self.add_working_days(start, duration - 1)?))
+        } else {
+            Some((start, self.previous_working_day(new.1).max(start)))
+        }
+    }
+
+    // Move `days` working days forward (or backward when negative), skipping non-working days.
+    // None beyond MAX_SCAN_DAYS or the representable date range.
+    pub fn add_working_days(&self, date: NaiveDate, days: i64) -> Option<NaiveDate> {
+        if days.abs() > MAX_SCAN_DAYS {
+            return None;
+        }
+        if self.work_week.is_empty() {
+            return date.checked_add_signed(Duration::days(days));
+        }
+        let mut date = date;
+        let mut remaining = days.abs();
+        while remaining > 0 {
+            date = if days > 0 { date.succ_opt()? } else { date.pred_opt()? };
+            if self.is_working_day(date) {
+                remaining -= 1;
+            }
+        }
+        Some(date)
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).unwrap()
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+// Japanese public holidays under the rules in force since 2020; empty outside JP_HOLIDAY_YEARS
+pub fn jp_holidays(year: i32) -> Vec<(NaiveDate, &'static str)> {
+    if !JP_HOLIDAY_YEARS.contains(&year) {
+        return Vec::new();
+    }
+    let offset = f64::from(year - 1980);
+    let leap = ((year - 1980) / 4) as f64;
+    let vernal = (20.8431 + 0.242194 * offset - leap).floor() as u32;
+    let autumnal = (23.2488 + 0.242194 * offset - leap).floor() as u32;
+
+    // The Olympic years moved three holidays
+    let (marine, sports, mountain) = match year {
+        2020 => (ymd(2020, 7, 23), ymd(2020, 7, 24), ymd(2020, 8, 10)),
+        2021 => (ymd(2021, 7, 22), ymd(2021, 7, 23), ymd(2021, 8, 8)),
+        _ => (
+            nth_weekday(year, 7, Weekday::Mon, 3),
+            nth_weekday(year, 10, Weekday::Mon, 2),
+            ymd(year, 8, 11),
+        ),
+    };
+
+    let mut holidays = vec![
+        (ymd(year, 1, 1), "元日"),
+        (nth_weekday(year, 1, Weekday::Mon, 2), "成人の日"),
+        (ymd(year, 2, 11), "建国記念の日"),
+        (ymd(year, 2, 23), "天皇誕生日"),
+        (ymd(year, 3, vernal), "春分の日"),
+        (ymd(year, 4, 29), "昭和の日"),
+        (ymd(year, 5, 3), "憲法記念日"),
+        (ymd(year, 5, 4), "みどりの日"),
+        (ymd(year, 5, 5), "こどもの日"),
+        (marine, "海の日"),
+        (mountain, "山の日"),
+        (nth_weekday(year, 9, Weekday::Mon, 3), "敬老の日"),
+        (ymd(year, 9, autumnal), "秋分の日"),
+        (sports, "スポーツの日"),
+        (ymd(year, 11, 3), "文化の日"),
+        (ymd(year, 11, 23), "勤労感謝の日"),
+    ];
+    holidays.sort_by_key(|(d, _)| *d);
+
+    let dates: HashSet<NaiveDate> = holidays.iter().map(|(d, _)| *d).collect();
+    let mut extra = Vec::new();
+
+    // A day sandwiched between two holidays is a holiday (国民の休日)
+    for (date, _) in &holidays {
+        let next = *date + Duration::days(1);
+        if dates.contains(&(next + Duration::days(1)))
+            && !dates.contains(&next)
+            && next.weekday() != Weekday::Sun
+        {
+            extra.push((next, "国民の休日"));
+        }
+    }
+
+    // A holiday on Sunday moves to the next non-holiday (振替休日)
+    for (date, _) in &holidays {
+        if date.weekday() == Weekday::Sun {
+            let mut substitute = *date + Duration::days(1);
+            while dates.contains(&substitute) {
+                substitute += Duration::days(1);
+            }
+            extra.push((substitute, "振替休日"));
+        }
+    }
+
+    holidays.extend(extra);
+    holidays.sort_by_key(|(d, _)| *d);
+    holidays
+}
+
+fn parse_holiday_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim().trim_matches('"');
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y%m%d"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(value, fmt).ok())
+}
+
+// Holidays from an iCalendar file (all-day VEVENTs)
+pub fn parse_ics(content: &str) -> Vec<Holiday> {
+    let mut holidays = Vec::new();
+    let mut date = None;
+    let mut name = String::new();
+    // Unfold continuation lines first
+    let unfolded = content.replace("\r\n", "\n").replace("\n ", "").replace("\n\t", "");
+    for line in unfolded.lines() {
+        if line == "BEGIN:VEVENT" {
+            date = None;
+            name.clear();
+        } else if line.starts_with("DTSTART") {
+            date = line.split(':').nth(1).and_then(|v| parse_holiday_date(v.get(..8).unwrap_or(v)));
+        } else if let Some(summary) = line.strip_prefix("SUMMARY") {
+            name = summary.split_once(':').map(|(_, v)| v.to_string()).unwrap_or_default();
+        } else if line == "END:VEVENT" {
+            if let Some(d) = date.take() {
+                holidays.push(Holiday {
+                    date: d.format("%Y-%m-%d").to_string(),
+                    name: name.clone(),
+                });
+            }
+        }
+    }
+    holidays
+}
+
+// Holidays from "date,name" CSV lines; rows without a valid date (e.g. a header) are skipped
+pub fn parse_csv(content: &str) -> Vec<Holiday> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (date, name) = line.split_once(',').unwrap_or((line, ""));
+            parse_holiday_date(date).map(|d| Holiday {
+                date: d.format("%Y-%m-%d").to_string(),
+                name: name.trim().trim_matches('"').to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(year: i32) -> HashMap<NaiveDate, &'static str> {
+        jp_holidays(year).into_iter().collect()
+    }
+
+    #[test]
+    fn jp_holidays_follow_the_computed_and_fixed_dates() {
+        let holidays = names(2024);
+        assert_eq!(holidays.len(), 21);
+        assert_eq!(holidays[&ymd(2024, 1, 8)], "成人の日");
+        assert_eq!(holidays[&ymd(2024, 3, 20)], "春分の日");
+        assert_eq!(holidays[&ymd(2024, 7, 15)], "海の日");
+        assert_eq!(holidays[&ymd(2024, 9, 22)], "秋分の日");
+        assert_eq!(holidays[&ymd(2024, 10, 14)], "スポーツの日");
+    }
+
+    #[test]
+    fn jp_holidays_apply_the_olympic_moves() {
+        let holidays = names(2021);
+        assert_eq!(holidays[&ymd(2021, 7, 22)], "海の日");
+        assert_eq!(holidays[&ymd(2021, 7, 23)], "スポーツの日");
+        assert_eq!(holidays[&ymd(2021, 8, 8)], "山の日");
+        assert!(!holidays.contains_key(&ymd(2021, 8, 11)));
+    }
+
+    #[test]
+    fn jp_holidays_are_empty_outside_the_supported_years() {
+        assert!(jp_holidays(2019).is_empty());
+        assert!(jp_holidays(2100).is_empty());
+        assert!(WorkCalendar::standard().is_working_day(ymd(2100, 1, 1)));
+    }
+
+    #[test]
+    fn sunday_holidays_get_a_substitute_on_the_next_free_day() {
+        let holidays = names(2024);
+        assert_eq!(holidays[&ymd(2024, 2, 12)], "振替休日");
+        assert_eq!(holidays[&ymd(2024, 9, 23)], "振替休日");
+        // Constitution Day weekend: 5/5 falls on Sunday and 5/6 is free
+        assert_eq!(holidays[&ymd(2024, 5, 6)], "振替休日");
+        // In 2026 5/3 is a Sunday, and 5/4 and 5/5 are already holidays
+        let holidays = names(2026);
+        assert_eq!(holidays[&ymd(2026, 5, 6)], "振替休日");
+        assert!(!holidays.contains_key(&ymd(2026, 5, 7)));
+    }
+
+    #[test]
+    fn a_day_between_two_holidays_becomes_a_holiday() {
+        let holidays = names(2026);
+        assert_eq!(holidays[&ymd(2026, 9, 21)], "敬老の日");
+        assert_eq!(holidays[&ymd(2026, 9, 22)], "国民の休日");
+        assert_eq!(holidays[&ymd(2026, 9, 23)], "秋分の日");
+    }
+
+    #[test]
+    fn moving_a_bar_keeps_its_working_days() {
+        let calendar = WorkCalendar::standard();
+        let old = (ymd(2024, 6, 3), ymd(2024, 6, 7));
+        // Dropped on a Saturday: starts on Monday and still spans five working days
+        assert_eq!(
+            calendar.reschedule(old, (ymd(2024, 6, 8), ymd(2024, 6, 12))),
+            Some((ymd(2024, 6, 10), ymd(2024, 6, 14)))
+        );
+        // Dropped onto Marine Day: the holiday is skipped at both ends
+        assert_eq!(
+            calendar.reschedule(old, (ymd(2024, 7, 15), ymd(2024, 7, 19))),
+            Some((ymd(2024, 7, 16), ymd(2024, 7, 22)))
+        );
+    }
+
+    #[test]
+    fn resizing_a_bar_snaps_both_ends() {
+        let calendar = WorkCalendar::standard();
+        let old = (ymd(2024, 6, 3), ymd(2024, 6, 7));
+        assert_eq!(
+            calendar.reschedule(old, (ymd(2024, 6, 3), ymd(2024, 6, 9))),
+            Some((ymd(2024, 6, 3), ymd(2024, 6, 7)))
+        );
+        // The end never lands before the start
+        assert_eq!(
+            calendar.reschedule(old, (ymd(2024, 6, 8), ymd(2024, 6, 8))),
+            Some((ymd(2024, 6, 10), ymd(2024, 6, 10)))
+        );
+    }
+
+    #[test]
+    fn a_calendar_without_working_days_does_not_stall() {
+        let calendar = WorkCalendar::new(
+            &Calendar {
+                id: "none".to_string(),
+                name: "None".to_string(),
+                work_week: Vec::new(),
+                use_jp_holidays: false,
+                created_at: String::new(),
+            },
+            &[],
+        );
+        let date = ymd(2024, 6, 3);
+        assert_eq!(calendar.next_working_day(date), date);
+        assert_eq!(calendar.previous_working_day(date), date);
+        assert_eq!(WorkCalendar::standard().add_working_days(date, MAX_SCAN_DAYS + 1), None);
+    }
+}
@@ -1,22 +1,78 @@
-use crate::db::{Database, Project, Task, DailyTodo, DailyTodoWithTask};
+use crate::attachments::{self, AttachmentStore};
+use crate::baseline::{self, BaselineComparison};
+use crate::calendar::{self, Calendar, Holiday, WorkCalendar};
+use crate::export::{self, ExportFormat};
+use crate::fields::{self, FieldType};
+use crate::db::{Database, Project, Task, TaskKind, TaskStatus, DailyTodo, DailyTodoWithTask, CarryOverMode, RecurringTodo, TimeEntry, TimeTotal, Assignee, TaskAssignment, Baseline, BaselineTask, Milestone, SearchFilters, SearchHit, TaskQuery, TaskPage, SavedView, Tag, TagLink, CustomField, TaskFieldValue, TaskActivity, TaskComment, Attachment, ChecklistItem, BulkItemResult, PatchOutcome, ProjectPatch, TaskPatch, ChangeSet, utc_timestamp};
+use crate::evm::{self, ProjectEarnedValue};
+use crate::recurrence::RecurrenceRule;
+use crate::workload::{self, AssigneeWorkload};
+use crate::report::{self, ReportFormat, ReportGrouping};
+use crate::suggest::{self, TodoSuggestion};
+use crate::todo_sync::TodoSyncRules;
+use chrono::{DateTime, NaiveDate};
 use tauri::State;
+use tauri_plugin_opener::OpenerExt;
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 type DbState = Arc<Database>;
+type StoreState = Arc<AttachmentStore>;
+
+// Largest day count accepted for look-ahead windows and date shifts
+const MAX_DAY_SPAN: i64 = 3650;
 
 #[derive(Debug, serde::Serialize)]
 pub struct CommandError {
     message: String,
+    // Machine-readable kind for errors the frontend handles specially, e.g. "conflict"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
 }
 
-impl From<rusqlite::Error> for CommandError {
-    fn from(err: rusqlite::Error) -> Self {
+impl CommandError {
+    fn new(message: impl Into<String>) -> Self {
+        CommandError {
+            message: message.into(),
+            code: None,
+        }
+    }
+
+    // A write based on an outdated version; the caller should reload and retry
+    fn conflict(message: impl Into<String>) -> Self {
         CommandError {
-            message: err.to_string(),
+            message: message.into(),
+            code: Some("conflict"),
         }
     }
 }
 
+impl From<rusqlite::Error> for CommandError {
+    fn from(err: rusqlite::Error) -> Self {
+        CommandError::new(err.to_string())
+    }
+}
+
+impl From<chrono::ParseError> for CommandError {
+    fn from(err: chrono::ParseError) -> Self {
+        CommandError::new(format!("Invalid date: {}", err))
+    }
+}
+
+// Inclusive YYYY-MM-DD range, in order and at most MAX_DAY_SPAN days long
+fn parse_date_range(start_date: &str, end_date: &str) -> Result<(NaiveDate, NaiveDate), CommandError> {
+    let start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d")?;
+    let end = NaiveDate::parse_from_str(end_date, "%Y-%m-%d")?;
+    if start > end {
+        return Err(CommandError::new("start_date must not be after end_date"));
+    }
+    if (end - start).num_days() > MAX_DAY_SPAN {
+        return Err(CommandError::new(format!("A date range may span at most {} days", MAX_DAY_SPAN)));
+    }
+    Ok((start, end))
+}
+
 // Project commands
 #[tauri::command]
 pub fn create_project(
@@ -62,12 +118,39 @@ pub fn update_project(
     ).map_err(|e| e.into())
 }
 
+// Writes only the fields present in `patch`, rejecting it if the project changed since `version`
+#[tauri::command]
+pub fn patch_project(db: State<DbState>, id: String, patch: ProjectPatch, version: i64) -> Result<Project, CommandError> {
+    match db.patch_project(&id, &patch, version)? {
+        PatchOutcome::Applied(project) => Ok(project),
+        PatchOutcome::Stale(current) => Err(CommandError::conflict(format!(
+            "Project was changed elsewhere (version {} is now {})",
+            version, current.version
+        ))),
+        PatchOutcome::NotFound => Err(CommandError::new("Project not found")),
+    }
+}
+
 #[tauri::command]
-pub fn delete_project(db: State<DbState>, id: String) -> Result<(), CommandError> {
-    db.delete_project(&id).map_err(|e| e.into())
+pub fn delete_project(db: State<DbState>, store: State<StoreState>, id: String) -> Result<(), CommandError> {
+    db.delete_project(&id)?;
+    cleanup_attachments(&db, &store)
 }
 
 // Task commands
+
+// Milestones have zero duration: a single date is used for both ends
+fn milestone_dates(
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<(Option<String>, Option<String>), CommandError> {
+    match (start_date, end_date) {
+        (Some(start), Some(end)) if start != end => Err(CommandError::new("A milestone must start and end on the same day")),
+        (Some(date), _) | (None, Some(date)) => Ok((Some(date.clone()), Some(date))),
+        (None, None) => Ok((None, None)),
+    }
+}
+
 #[tauri::command]
 pub fn create_task(
     db: State<DbState>,
@@ -75,20 +158,34 @@ pub fn create_task(
     parent_id: Option<String>,
     title: String,
     description: Option<String>,
-    status: String,
+    status: TaskStatus,
     priority: i32,
     start_date: Option<String>,
     end_date: Option<String>,
+    kind: Option<TaskKind>,
 ) -> Result<Task, CommandError> {
+    let kind = kind.unwrap_or(TaskKind::Task);
+    if let Some(ref parent_id) = parent_id {
+        if db.get_task(parent_id)?.is_some_and(|p| p.kind == TaskKind::Milestone) {
+            return Err(CommandError::new("Milestones cannot have child tasks"));
+        }
+    }
+    let (start_date, end_date) = if kind == TaskKind::Milestone {
+        milestone_dates(start_date, end_date)?
+    } else {
+        (start_date, end_date)
+    };
+
     db.create_task(
         &project_id,
         parent_id.as_deref(),
         &title,
         description.as_deref(),
-        &status,
+        status,
         priority,
         start_date.as_deref(),
         end_date.as_deref(),
+        kind,
     ).map_err(|e| e.into())
 }
 
@@ -103,37 +200,702 @@ pub fn update_task(
     id: String,
     title: String,
     description: Option<String>,
-    status: String,
+    status: TaskStatus,
     priority: i32,
     start_date: Option<String>,
     end_date: Option<String>,
     progress: i32,
 ) -> Result<(), CommandError> {
+    let mut fields = TaskPatch {
+        start_date: Some(start_date),
+        end_date: Some(end_date),
+        progress: Some(progress),
+        ..Default::default()
+    };
+    validate_task_patch(&db, &id, &mut fields)?;
+
     db.update_task(
         &id,
         &title,
         description.as_deref(),
-        &status,
+        status,
         priority,
-        start_date.as_deref(),
-        end_date.as_deref(),
+        fields.start_date.flatten().as_deref(),
+        fields.end_date.flatten().as_deref(),
         progress,
     ).map_err(|e| e.into())
 }
 
+fn parse_optional_date(date: &Option<String>) -> Result<Option<NaiveDate>, CommandError> {
+    Ok(date.as_deref().map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d")).transpose()?)
+}
+
+// Checks shared by full updates and patches. Only values that differ from the stored task
+// count as edits, so a full update may resend a summary's dates or checklist-driven progress.
+fn validate_task_patch(db: &Database, id: &str, patch: &mut TaskPatch) -> Result<(), CommandError> {
+    if patch.progress.is_some_and(|p| !(0..=100).contains(&p)) {
+        return Err(CommandError::new("progress must be between 0 and 100"));
+    }
+    let task = db.get_task(id)?.ok_or_else(|| CommandError::new("Task not found"))?;
+    if patch.progress.is_some_and(|p| p != task.progress)
+        && task.progress_from_checklist
+        && !db.get_checklist_items(id)?.is_empty()
+    {
+        return Err(CommandError::new("Progress follows the task's checklist"));
+    }
+    let dates_changed = patch.start_date.as_ref().is_some_and(|d| *d != task.start_date)
+        || patch.end_date.as_ref().is_some_and(|d| *d != task.end_date);
+    if dates_changed && task.kind == TaskKind::Summary {
+        return Err(CommandError::new("Summary dates follow their child tasks"));
+    }
+    for date in [&patch.start_date, &patch.end_date].into_iter().flatten() {
+        parse_optional_date(date)?;
+    }
+    // Moving one end of a milestone moves the other with it
+    if task.kind == TaskKind::Milestone && (patch.start_date.is_some() || patch.end_date.is_some()) {
+        let (start, end) = milestone_dates(patch.start_date.clone().flatten(), patch.end_date.clone().flatten())?;
+        patch.start_date = Some(start);
+        patch.end_date = Some(end);
+    }
+    Ok(())
+}
+
+// Writes only the fields present in `patch`, rejecting it if the task changed since `version`
+#[tauri::command]
+pub fn patch_task(db: State<DbState>, id: String, mut patch: TaskPatch, version: i64) -> Result<Task, CommandError> {
+    validate_task_patch(&db, &id, &mut patch)?;
+
+    match db.patch_task(&id, &patch, version)? {
+        PatchOutcome::Applied(task) => Ok(task),
+        PatchOutcome::Stale(current) => Err(CommandError::conflict(format!(
+            "Task was changed elsewhere (version {} is now {})",
+            version, current.version
+        ))),
+        PatchOutcome::NotFound => Err(CommandError::new("Task not found")),
+    }
+}
+
+// With `working_days`, a moved bar keeps its length in working days and both ends land on working days
 #[tauri::command]
 pub fn update_task_dates(
     db: State<DbState>,
     id: String,
     start_date: Option<String>,
     end_date: Option<String>,
+    working_days: Option<bool>,
+) -> Result<Task, CommandError> {
+    let task = db.get_task(&id)?.ok_or_else(|| CommandError::new("Task not found"))?;
+    // Dragging a milestone moves it as a whole
+    let (mut start_date, mut end_date) = if task.kind == TaskKind::Milestone {
+        milestone_dates(start_date.or(end_date.clone()), None)?
+    } else {
+        (start_date, end_date)
+    };
+
+    if working_days.unwrap_or(false) {
+        if let (Some(new_start), Some(new_end)) = (parse_optional_date(&start_date)?, parse_optional_date(&end_date)?) {
+            let old = match (parse_optional_date(&task.start_date)?, parse_optional_date(&task.end_date)?) {
+                (Some(old_start), Some(old_end)) => (old_start, old_end),
+                _ => (new_start, new_end),
+            };
+            let (calendar, holidays) = db.load_project_calendar(&task.project_id)?;
+            let (start, end) = WorkCalendar::new(&calendar, &holidays)
+                .reschedule(old, (new_start, new_end))
+                .ok_or_else(|| CommandError::new("Date out of range"))?;
+            start_date = Some(start.format("%Y-%m-%d").to_string());
+            end_date = Some(end.format("%Y-%m-%d").to_string());
+        }
+    }
+
+    db.update_task_dates(&id, start_date.as_deref(), end_date.as_deref())?;
+    db.get_task(&id)?.ok_or_else(|| CommandError::new("Task not found"))
+}
+
+#[tauri::command]
+pub fn update_task_estimate(db: State<DbState>, id: String, estimate_hours: Option<f64>) -> Result<(), CommandError> {
+    if estimate_hours.is_some_and(|h| !h.is_finite() || h < 0.0) {
+        return Err(CommandError::new("estimate_hours must be a non-negative number"));
+    }
+    db.update_task_estimate(&id, estimate_hours).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn set_task_kind(db: State<DbState>, id: String, kind: TaskKind) -> Result<Task, CommandError> {
+    if kind == TaskKind::Milestone && db.count_child_tasks(&id)? > 0 {
+        return Err(CommandError::new("A task with children cannot become a milestone"));
+    }
+    db.set_task_kind(&id, kind)?;
+    db.get_task(&id)?.ok_or_else(|| CommandError::new("Task not found"))
+}
+
+// Milestones across all projects due within the next `days` days (default 14)
+#[tauri::command]
+pub fn get_upcoming_milestones(db: State<DbState>, days: Option<i64>) -> Result<Vec<Milestone>, CommandError> {
+    let today = chrono::Local::now().date_naive();
+    let until = today + chrono::Duration::days(days.unwrap_or(14).clamp(0, MAX_DAY_SPAN));
+    db.get_milestones_between(&today.format("%Y-%m-%d").to_string(), &until.format("%Y-%m-%d").to_string())
+        .map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_task(db: State<DbState>, store: State<StoreState>, id: String) -> Result<(), CommandError> {
+    db.delete_task(&id)?;
+    cleanup_attachments(&db, &store)
+}
+
+// Planned/earned value, actual cost, SPI/CPI and EAC rolled up through the WBS
+#[tauri::command]
+pub fn project_earned_value(
+    db: State<DbState>,
+    project_id: String,
+    as_of: Option<String>,
+) -> Result<ProjectEarnedValue, CommandError> {
+    let as_of = match as_of {
+        Some(date) => NaiveDate::parse_from_str(&date, "%Y-%m-%d")?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let tasks = db.get_tasks_by_project(&project_id)?;
+    let actuals = db.get_actual_seconds_by_task(&project_id, &as_of.format("%Y-%m-%d").to_string())?;
+    let (calendar, holidays) = db.load_project_calendar(&project_id)?;
+    let work_calendar = WorkCalendar::new(&calendar, &holidays);
+    Ok(evm::project_earned_value(&project_id, &tasks, &actuals, as_of, &work_calendar))
+}
+
+// Search across project, task and todo text
+#[tauri::command]
+pub fn search(db: State<DbState>, query: String, filters: Option<SearchFilters>) -> Result<Vec<SearchHit>, CommandError> {
+    let filters = filters.unwrap_or_default();
+    for date in [&filters.date_from, &filters.date_to].into_iter().flatten() {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+    }
+    db.search(&query, &filters).map_err(|e| e.into())
+}
+
+// Bulk commands
+#[tauri::command]
+pub fn bulk_set_task_status(db: State<DbState>, ids: Vec<String>, status: TaskStatus) -> Result<Vec<BulkItemResult>, CommandError> {
+    db.bulk_set_task_status(&ids, status).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn bulk_set_task_priority(db: State<DbState>, ids: Vec<String>, priority: i32) -> Result<Vec<BulkItemResult>, CommandError> {
+    db.bulk_set_task_priority(&ids, priority).map_err(|e| e.into())
+}
+
+// Moves tasks by `days`; with `working_days` the shift counts working days of each task's
+// project calendar and bars keep their length in working days
+#[tauri::command]
+pub fn bulk_shift_task_dates(
+    db: State<DbState>,
+    ids: Vec<String>,
+    days: i64,
+    working_days: Option<bool>,
+) -> Result<Vec<BulkItemResult>, CommandError> {
+    if days.abs() > MAX_DAY_SPAN {
+        return Err(CommandError::new(format!("days must be between -{0} and {0}", MAX_DAY_SPAN)));
+    }
+    // Calendars are loaded up front; the shift itself runs while the database is locked
+    let mut calendars = HashMap::new();
+    if working_days.unwrap_or(false) {
+        for id in &ids {
+            if let Some(task) = db.get_task(id)? {
+                if !calendars.contains_key(&task.project_id) {
+                    let (calendar, holidays) = db.load_project_calendar(&task.project_id)?;
+                    calendars.insert(task.project_id.clone(), WorkCalendar::new(&calendar, &holidays));
+                }
+            }
+        }
+    }
+
+    db.bulk_shift_task_dates(&ids, |task| {
+        if task.kind == TaskKind::Summary {
+            return Err("Summary dates follow their child tasks".to_string());
+        }
+        let start = parse_optional_date(&task.start_date).map_err(|e| e.message)?;
+        let end = parse_optional_date(&task.end_date).map_err(|e| e.message)?;
+        let calendar = calendars.get(&task.project_id);
+        let shift = |date: NaiveDate| {
+            match calendar {
+                Some(calendar) => calendar.add_working_days(calendar.next_working_day(date), days),
+                None => date.checked_add_signed(chrono::Duration::days(days)),
+            }
+            .ok_or_else(|| "Date out of range".to_string())
+        };
+
+        let (start, end) = match (start, end, calendar) {
+            (Some(start), Some(end), Some(calendar)) => {
+                let new_start = shift(start)?;
+                let (start, end) = new_start
+                    .checked_add_signed(end - start)
+                    .and_then(|new_end| calendar.reschedule((start, end), (new_start, new_end)))
+                    .ok_or_else(|| "Date out of range".to_string())?;
+                (Some(start), Some(end))
+            }
+            (start, end, _) => (start.map(shift).transpose()?, end.map(shift).transpose()?),
+        };
+        let format = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+        Ok((start.map(format), end.map(format)))
+    })
+    .map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn bulk_delete_tasks(db: State<DbState>, store: State<StoreState>, ids: Vec<String>) -> Result<Vec<BulkItemResult>, CommandError> {
+    let results = db.bulk_delete_tasks(&ids)?;
+    cleanup_attachments(&db, &store)?;
+    Ok(results)
+}
+
+#[tauri::command]
+pub fn bulk_add_tasks_to_todos(db: State<DbState>, ids: Vec<String>, date: String) -> Result<Vec<BulkItemResult>, CommandError> {
+    NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+    db.bulk_add_tasks_to_todos(&ids, &date).map_err(|e| e.into())
+}
+
+// Task query commands
+fn validate_task_query(query: &TaskQuery) -> Result<(), CommandError> {
+    for date in [&query.window_start, &query.window_end].into_iter().flatten() {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+    }
+    if query.due_within_days.is_some_and(|days| !(0..=MAX_DAY_SPAN).contains(&days)) {
+        return Err(CommandError::new(format!("due_within_days must be between 0 and {}", MAX_DAY_SPAN)));
+    }
+    Ok(())
+}
+
+// Filtered, sorted and paginated tasks across all projects
+#[tauri::command]
+pub fn query_tasks(db: State<DbState>, query: TaskQuery) -> Result<TaskPage, CommandError> {
+    validate_task_query(&query)?;
+    db.query_tasks(&query).map_err(|e| e.into())
+}
+
+// Saved view commands
+#[tauri::command]
+pub fn create_saved_view(db: State<DbState>, name: String, query: TaskQuery) -> Result<SavedView, CommandError> {
+    validate_task_query(&query)?;
+    db.create_saved_view(&name, &query).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_saved_views(db: State<DbState>) -> Result<Vec<SavedView>, CommandError> {
+    db.get_saved_views().map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn update_saved_view(db: State<DbState>, id: String, name: String, query: TaskQuery) -> Result<(), CommandError> {
+    validate_task_query(&query)?;
+    db.update_saved_view(&id, &name, &query).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn reorder_saved_views(db: State<DbState>, ids: Vec<String>) -> Result<(), CommandError> {
+    db.reorder_saved_views(&ids).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_saved_view(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    db.delete_saved_view(&id).map_err(|e| e.into())
+}
+
+// Runs the view's stored query; `limit`/`offset` page through it without changing the view
+#[tauri::command]
+pub fn evaluate_saved_view(
+    db: State<DbState>,
+    id: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<TaskPage, CommandError> {
+    let view = db.get_saved_view(&id)?.ok_or_else(|| CommandError::new("Saved view not found"))?;
+    let query = TaskQuery {
+        limit: limit.or(view.query.limit),
+        offset: offset.or(view.query.offset),
+        ..view.query
+    };
+    db.query_tasks(&query).map_err(|e| e.into())
+}
+
+// Tag commands
+fn validate_tag(name: &str, color: &str) -> Result<(), CommandError> {
+    if name.trim().is_empty() {
+        return Err(CommandError::new("Tag name must not be empty"));
+    }
+    let hex = color.strip_prefix('#').unwrap_or("");
+    if !matches!(hex.len(), 3 | 6) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(CommandError::new("Tag color must be a hex color such as #e5484d"));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn create_tag(db: State<DbState>, name: String, color: String) -> Result<Tag, CommandError> {
+    validate_tag(&name, &color)?;
+    db.create_tag(name.trim(), &color).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_all_tags(db: State<DbState>) -> Result<Vec<Tag>, CommandError> {
+    db.get_all_tags().map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn update_tag(db: State<DbState>, id: String, name: String, color: String) -> Result<(), CommandError> {
+    validate_tag(&name, &color)?;
+    db.update_tag(&id, name.trim(), &color).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_tag(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    db.delete_tag(&id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn tag_task(db: State<DbState>, task_id: String, tag_id: String) -> Result<(), CommandError> {
+    db.tag_task(&task_id, &tag_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn untag_task(db: State<DbState>, task_id: String, tag_id: String) -> Result<(), CommandError> {
+    db.untag_task(&task_id, &tag_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn tag_todo(db: State<DbState>, todo_id: String, tag_id: String) -> Result<(), CommandError> {
+    db.tag_todo(&todo_id, &tag_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn untag_todo(db: State<DbState>, todo_id: String, tag_id: String) -> Result<(), CommandError> {
+    db.untag_todo(&todo_id, &tag_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_task_tag_links(db: State<DbState>, project_id: String) -> Result<Vec<TagLink>, CommandError> {
+    db.get_task_tag_links(&project_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_todos_by_tag(
+    db: State<DbState>,
+    tag_id: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<DailyTodoWithTask>, CommandError> {
+    db.get_todos_by_tag(&tag_id, start_date.as_deref(), end_date.as_deref()).map_err(|e| e.into())
+}
+
+// Custom field commands
+fn validate_field_options(field_type: FieldType, options: &[String]) -> Result<(), CommandError> {
+    if field_type == FieldType::Enum && options.iter().all(|o| o.trim().is_empty()) {
+        return Err(CommandError::new("An enum field needs at least one option"));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn create_custom_field(
+    db: State<DbState>,
+    project_id: String,
+    name: String,
+    field_type: FieldType,
+    options: Option<Vec<String>>,
+) -> Result<CustomField, CommandError> {
+    let options = options.unwrap_or_default();
+    validate_field_options(field_type, &options)?;
+    db.create_custom_field(&project_id, &name, field_type, &options).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_custom_fields(db: State<DbState>, project_id: String) -> Result<Vec<CustomField>, CommandError> {
+    db.get_custom_fields(&project_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn update_custom_field(
+    db: State<DbState>,
+    id: String,
+    name: String,
+    options: Option<Vec<String>>,
+) -> Result<(), CommandError> {
+    let field = db.get_custom_field(&id)?.ok_or_else(|| CommandError::new("Custom field not found"))?;
+    let options = options.unwrap_or_default();
+    validate_field_options(field.field_type, &options)?;
+    db.update_custom_field(&id, &name, &options).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_custom_field(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    db.delete_custom_field(&id).map_err(|e| e.into())
+}
+
+// Validates the value against the field's type; an empty or missing value clears it
+#[tauri::command]
+pub fn set_task_field_value(
+    db: State<DbState>,
+    task_id: String,
+    field_id: String,
+    value: Option<String>,
+) -> Result<Option<String>, CommandError> {
+    let field = db.get_custom_field(&field_id)?.ok_or_else(|| CommandError::new("Custom field not found"))?;
+    let task = db.get_task(&task_id)?.ok_or_else(|| CommandError::new("Task not found"))?;
+    if task.project_id != field.project_id {
+        return Err(CommandError::new("The field belongs to a different project"));
+    }
+
+    let value = match value.filter(|v| !v.trim().is_empty()) {
+        Some(raw) => Some(fields::normalize_value(field.field_type, &field.options, &raw).map_err(CommandError::new)?),
+        None => None,
+    };
+    db.set_task_field_value(&task_id, &field_id, value.as_deref())?;
+    Ok(value)
+}
+
+#[tauri::command]
+pub fn get_task_field_values(db: State<DbState>, project_id: String) -> Result<Vec<TaskFieldValue>, CommandError> {
+    db.get_task_field_values(&project_id).map_err(|e| e.into())
+}
+
+// Project tasks with their custom fields as CSV (default) or JSON
+#[tauri::command]
+pub fn export_tasks(db: State<DbState>, project_id: String, format: Option<ExportFormat>) -> Result<String, CommandError> {
+    let tasks = db.get_tasks_by_project(&project_id)?;
+    let fields = db.get_custom_fields(&project_id)?;
+    let values = db.get_task_field_values(&project_id)?;
+    Ok(match format.unwrap_or_default() {
+        ExportFormat::Csv => export::tasks_csv(&tasks, &fields, &values),
+        ExportFormat::Json => export::tasks_json(&tasks, &fields, &values),
+    })
+}
+
+// Sync commands
+
+// Projects, tasks and todos written at or after `since` (RFC 3339, any offset), plus deletions
+#[tauri::command]
+pub fn get_changes_since(db: State<DbState>, since: String) -> Result<ChangeSet, CommandError> {
+    let since = utc_timestamp(&DateTime::parse_from_rfc3339(&since)?);
+    Ok(db.get_changes_since(&since)?)
+}
+
+// Checklist commands
+#[tauri::command]
+pub fn add_checklist_item(db: State<DbState>, task_id: String, title: String) -> Result<ChecklistItem, CommandError> {
+    if title.trim().is_empty() {
+        return Err(CommandError::new("Checklist item must not be empty"));
+    }
+    db.add_checklist_item(&task_id, &title).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_checklist_items(db: State<DbState>, task_id: String) -> Result<Vec<ChecklistItem>, CommandError> {
+    db.get_checklist_items(&task_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn update_checklist_item(db: State<DbState>, id: String, title: String) -> Result<(), CommandError> {
+    if title.trim().is_empty() {
+        return Err(CommandError::new("Checklist item must not be empty"));
+    }
+    db.update_checklist_item(&id, &title).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn toggle_checklist_item(db: State<DbState>, id: String) -> Result<bool, CommandError> {
+    db.toggle_checklist_item(&id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn reorder_checklist_items(db: State<DbState>, task_id: String, ids: Vec<String>) -> Result<(), CommandError> {
+    db.reorder_checklist_items(&task_id, &ids).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_checklist_item(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    db.delete_checklist_item(&id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn set_progress_from_checklist(db: State<DbState>, task_id: String, enabled: bool) -> Result<(), CommandError> {
+    db.set_progress_from_checklist(&task_id, enabled).map_err(|e| e.into())
+}
+
+// Attachment commands
+fn io_error(err: std::io::Error) -> CommandError {
+    CommandError::new(err.to_string())
+}
+
+// Removes attachment rows of deleted tasks and files no attachment refers to any more
+pub fn cleanup_attachments(db: &Database, store: &AttachmentStore) -> Result<(), CommandError> {
+    let referenced = db.prune_attachments()?;
+    store.remove_unreferenced(&referenced).map_err(io_error)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn attach_file(
+    db: State<DbState>,
+    store: State<StoreState>,
+    task_id: String,
+    path: String,
+) -> Result<Attachment, CommandError> {
+    db.get_task(&task_id)?.ok_or_else(|| CommandError::new("Task not found"))?;
+    let source = Path::new(&path);
+    let name = source
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| CommandError::new("Invalid file path"))?;
+    let stored = store.store(source).map_err(io_error)?;
+    db.add_attachment(&task_id, name, stored.size, attachments::mime_type(name), &stored.hash)
+        .map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_attachments(db: State<DbState>, task_id: String) -> Result<Vec<Attachment>, CommandError> {
+    db.get_attachments(&task_id).map_err(|e| e.into())
+}
+
+// Opens a copy named after the original file with the system's default application
+#[tauri::command]
+pub fn open_attachment(
+    app: tauri::AppHandle,
+    db: State<DbState>,
+    store: State<StoreState>,
+    id: String,
 ) -> Result<(), CommandError> {
-    db.update_task_dates(&id, start_date.as_deref(), end_date.as_deref()).map_err(|e| e.into())
+    let attachment = db.get_attachment(&id)?.ok_or_else(|| CommandError::new("Attachment not found"))?;
+    let path = store.export_for_opening(&attachment.hash, &attachment.name).map_err(io_error)?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| CommandError::new(e.to_string()))
 }
 
 #[tauri::command]
-pub fn delete_task(db: State<DbState>, id: String) -> Result<(), CommandError> {
-    db.delete_task(&id).map_err(|e| e.into())
+pub fn delete_attachment(db: State<DbState>, store: State<StoreState>, id: String) -> Result<(), CommandError> {
+    db.delete_attachment(&id)?;
+    cleanup_attachments(&db, &store)
+}
+
+// Copies the database and attachment store into a new timestamped folder under `target_dir`
+#[tauri::command]
+pub fn backup_data(db: State<DbState>, store: State<StoreState>, target_dir: String) -> Result<String, CommandError> {
+    let folder = Path::new(&target_dir).join(format!(
+        "todo-wbs-backup-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::create_dir_all(&folder).map_err(io_error)?;
+    db.backup_to(&folder.join("data.db").to_string_lossy())?;
+    if store.root().exists() {
+        attachments::copy_dir_all(store.root(), &folder.join("attachments")).map_err(io_error)?;
+    }
+    Ok(folder.to_string_lossy().to_string())
+}
+
+// Comment commands
+#[tauri::command]
+pub fn add_task_comment(
+    db: State<DbState>,
+    task_id: String,
+    body: String,
+    parent_id: Option<String>,
+    author: Option<String>,
+) -> Result<TaskComment, CommandError> {
+    if body.trim().is_empty() {
+        return Err(CommandError::new("Comment must not be empty"));
+    }
+    db.get_task(&task_id)?.ok_or_else(|| CommandError::new("Task not found"))?;
+    if let Some(ref parent_id) = parent_id {
+        let parent = db.get_task_comment(parent_id)?.ok_or_else(|| CommandError::new("Parent comment not found"))?;
+        if parent.task_id != task_id {
+            return Err(CommandError::new("Replies must be on the same task"));
+        }
+    }
+    db.add_task_comment(&task_id, parent_id.as_deref(), author.as_deref(), &body).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn update_task_comment(db: State<DbState>, id: String, body: String) -> Result<(), CommandError> {
+    if body.trim().is_empty() {
+        return Err(CommandError::new("Comment must not be empty"));
+    }
+    db.update_task_comment(&id, &body).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_task_comment(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    db.delete_task_comment(&id).map_err(|e| e.into())
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEntry {
+    Comment(TaskComment),
+    Activity(TaskActivity),
+}
+
+impl TimelineEntry {
+    fn at(&self) -> &str {
+        match self {
+            TimelineEntry::Comment(c) => &c.created_at,
+            TimelineEntry::Activity(a) => &a.changed_at,
+        }
+    }
+}
+
+// Comments and field changes of a task in chronological order
+#[tauri::command]
+pub fn get_task_timeline(db: State<DbState>, task_id: String) -> Result<Vec<TimelineEntry>, CommandError> {
+    let mut timeline: Vec<TimelineEntry> = db
+        .get_task_comments(&task_id)?
+        .into_iter()
+        .map(TimelineEntry::Comment)
+        .chain(db.get_task_activity(&task_id)?.into_iter().map(TimelineEntry::Activity))
+        .collect();
+    timeline.sort_by(|a, b| a.at().cmp(b.at()));
+    Ok(timeline)
+}
+
+// Baseline commands
+#[tauri::command]
+pub fn create_baseline(db: State<DbState>, project_id: String, name: String) -> Result<Baseline, CommandError> {
+    db.create_baseline(&project_id, &name).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_baselines(db: State<DbState>, project_id: String) -> Result<Vec<Baseline>, CommandError> {
+    db.get_baselines(&project_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn rename_baseline(db: State<DbState>, id: String, name: String) -> Result<(), CommandError> {
+    db.rename_baseline(&id, &name).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_baseline(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    db.delete_baseline(&id).map_err(|e| e.into())
+}
+
+// Snapshot rows, e.g. for drawing baseline ghost bars on the Gantt chart
+#[tauri::command]
+pub fn get_baseline_tasks(db: State<DbState>, baseline_id: String) -> Result<Vec<BaselineTask>, CommandError> {
+    db.get_baseline_tasks(&baseline_id).map_err(|e| e.into())
+}
+
+// Start/finish slippage in working days and progress variance per task
+#[tauri::command]
+pub fn compare_baseline(
+    db: State<DbState>,
+    project_id: String,
+    baseline_id: String,
+) -> Result<BaselineComparison, CommandError> {
+    let baseline = db
+        .get_baseline(&baseline_id)?
+        .filter(|b| b.project_id == project_id)
+        .ok_or_else(|| CommandError::new("Baseline not found for this project"))?;
+    let snapshot = db.get_baseline_tasks(&baseline_id)?;
+    let tasks = db.get_tasks_by_project(&project_id)?;
+    let (calendar, holidays) = db.load_project_calendar(&project_id)?;
+    let work_calendar = WorkCalendar::new(&calendar, &holidays);
+    Ok(baseline::compare(baseline, &snapshot, &tasks, &work_calendar))
 }
 
 // Daily TODO commands
@@ -168,6 +930,58 @@ pub fn update_todo_memo(db: State<DbState>, id: String, memo: Option<String>) ->
     db.update_todo_memo(&id, memo.as_deref()).map_err(|e| e.into())
 }
 
+// Ranked todo candidates for `date` from open leaf tasks not yet on that day's list
+#[tauri::command]
+pub fn suggest_todos(db: State<DbState>, date: String, limit: Option<usize>) -> Result<Vec<TodoSuggestion>, CommandError> {
+    let target = chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+    let tasks = db.get_open_leaf_tasks()?;
+    let projects = db.get_all_projects()?;
+    let planned = db.get_task_ids_with_todos_on(&date)?;
+
+    let mut suggestions = suggest::suggest_todos(target, &tasks, &projects, &planned);
+    suggestions.truncate(limit.unwrap_or(20));
+    Ok(suggestions)
+}
+
+#[tauri::command]
+pub fn set_todo_final(db: State<DbState>, id: String, is_final: bool) -> Result<(), CommandError> {
+    db.set_todo_final(&id, is_final).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_todo_sync_rules(db: State<DbState>) -> Result<TodoSyncRules, CommandError> {
+    db.get_sync_rules().map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn set_todo_sync_rules(db: State<DbState>, rules: TodoSyncRules) -> Result<(), CommandError> {
+    db.set_sync_rules(&rules).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn update_todo_schedule(
+    db: State<DbState>,
+    id: String,
+    priority: i32,
+    scheduled_time: Option<String>,
+    duration_minutes: Option<i32>,
+) -> Result<(), CommandError> {
+    // Normalized to "HH:MM" so lexical order matches time order
+    let scheduled_time = scheduled_time
+        .map(|t| chrono::NaiveTime::parse_from_str(&t, "%H:%M").map(|t| t.format("%H:%M").to_string()))
+        .transpose()?;
+    if duration_minutes.is_some_and(|m| m <= 0) {
+        return Err(CommandError::new("duration_minutes must be positive"));
+    }
+    db.update_todo_schedule(&id, priority, scheduled_time.as_deref(), duration_minutes).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn reorder_todos(db: State<DbState>, date: String, ids: Vec<String>) -> Result<(), CommandError> {
+    chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+    db.reorder_todos(&date, &ids).map_err(|e| e.into())
+}
+
 #[tauri::command]
 pub fn delete_todo(db: State<DbState>, id: String) -> Result<(), CommandError> {
     db.delete_todo(&id).map_err(|e| e.into())
@@ -178,53 +992,393 @@ pub fn add_task_to_todo(db: State<DbState>, task_id: String, date: String) -> Re
     db.add_task_to_todo(&task_id, &date).map_err(|e| e.into())
 }
 
-// Export daily report as markdown
 #[tauri::command]
-pub fn generate_daily_report(db: State<DbState>, date: String, memo: String) -> Result<String, CommandError> {
-    let todos = db.get_todos_by_date(&date)?;
-    
-    let completed: Vec<_> = todos.iter().filter(|t| t.completed).collect();
-    let incomplete: Vec<_> = todos.iter().filter(|t| !t.completed).collect();
-    
-    let mut report = format!("# 日報 - {}\n\n", date);
-    
-    report.push_str("## 完了したタスク\n");
-    if completed.is_empty() {
-        report.push_str("なし\n");
-    } else {
-        for todo in completed {
-            let prefix = if let Some(ref project) = todo.project_name {
-                format!("{}: ", project)
-            } else {
-                String::new()
-            };
-            report.push_str(&format!("- [x] {}{}\n", prefix, todo.title));
-            if let Some(ref m) = todo.memo {
-                if !m.is_empty() {
-                    report.push_str(&format!("  - {}\n", m));
-                }
-            }
+pub fn carry_over_todos(
+    db: State<DbState>,
+    from_date: String,
+    to_date: String,
+    mode: Option<CarryOverMode>,
+) -> Result<Vec<DailyTodo>, CommandError> {
+    chrono::NaiveDate::parse_from_str(&from_date, "%Y-%m-%d")?;
+    chrono::NaiveDate::parse_from_str(&to_date, "%Y-%m-%d")?;
+    db.carry_over_todos(&from_date, &to_date, mode.unwrap_or(CarryOverMode::Move)).map_err(|e| e.into())
+}
+
+// Recurring TODO commands
+fn validate_recurrence_dates(start_date: &str, end_date: Option<&str>) -> Result<(), CommandError> {
+    let start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d")?;
+    if let Some(end_date) = end_date {
+        if NaiveDate::parse_from_str(end_date, "%Y-%m-%d")? < start {
+            return Err(CommandError::new("end_date must not be before start_date"));
         }
     }
-    
-    report.push_str("\n## 未完了のタスク\n");
-    if incomplete.is_empty() {
-        report.push_str("なし\n");
-    } else {
-        for todo in incomplete {
-            let prefix = if let Some(ref project) = todo.project_name {
-                format!("{}: ", project)
-            } else {
-                String::new()
-            };
-            report.push_str(&format!("- [ ] {}{}\n", prefix, todo.title));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn create_recurring_todo(
+    db: State<DbState>,
+    task_id: Option<String>,
+    title: String,
+    memo: Option<String>,
+    rule: RecurrenceRule,
+    start_date: String,
+    end_date: Option<String>,
+) -> Result<RecurringTodo, CommandError> {
+    rule.validate().map_err(CommandError::new)?;
+    validate_recurrence_dates(&start_date, end_date.as_deref())?;
+    db.create_recurring_todo(
+        task_id.as_deref(),
+        &title,
+        memo.as_deref(),
+        &rule,
+        &start_date,
+        end_date.as_deref(),
+    ).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_recurring_todos(db: State<DbState>) -> Result<Vec<RecurringTodo>, CommandError> {
+    db.get_recurring_todos().map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn update_recurring_todo(
+    db: State<DbState>,
+    id: String,
+    task_id: Option<String>,
+    title: String,
+    memo: Option<String>,
+    rule: RecurrenceRule,
+    start_date: String,
+    end_date: Option<String>,
+) -> Result<(), CommandError> {
+    rule.validate().map_err(CommandError::new)?;
+    validate_recurrence_dates(&start_date, end_date.as_deref())?;
+    db.update_recurring_todo(
+        &id,
+        task_id.as_deref(),
+        &title,
+        memo.as_deref(),
+        &rule,
+        &start_date,
+        end_date.as_deref(),
+    ).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_recurring_todo(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    db.delete_recurring_todo(&id).map_err(|e| e.into())
+}
+
+// Time tracking commands
+#[tauri::command]
+pub fn start_timer(
+    db: State<DbState>,
+    todo_id: Option<String>,
+    task_id: Option<String>,
+    note: Option<String>,
+) -> Result<TimeEntry, CommandError> {
+    db.start_timer(todo_id.as_deref(), task_id.as_deref(), note.as_deref()).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn stop_timer(db: State<DbState>) -> Result<Option<TimeEntry>, CommandError> {
+    db.stop_timer().map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_running_timer(db: State<DbState>) -> Result<Option<TimeEntry>, CommandError> {
+    db.get_running_timer().map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn add_time_entry(
+    db: State<DbState>,
+    todo_id: Option<String>,
+    task_id: Option<String>,
+    started_at: String,
+    ended_at: String,
+    note: Option<String>,
+) -> Result<TimeEntry, CommandError> {
+    let start = DateTime::parse_from_rfc3339(&started_at)?;
+    let end = DateTime::parse_from_rfc3339(&ended_at)?;
+    if start > end {
+        return Err(CommandError::new("started_at must not be after ended_at"));
+    }
+    db.add_time_entry(
+        todo_id.as_deref(),
+        task_id.as_deref(),
+        &utc_timestamp(&start),
+        &utc_timestamp(&end),
+        note.as_deref(),
+    ).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_time_entries(
+    db: State<DbState>,
+    todo_id: Option<String>,
+    task_id: Option<String>,
+) -> Result<Vec<TimeEntry>, CommandError> {
+    db.get_time_entries(todo_id.as_deref(), task_id.as_deref()).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_time_entry(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    db.delete_time_entry(&id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_time_by_task(db: State<DbState>, start_date: String, end_date: String) -> Result<Vec<TimeTotal>, CommandError> {
+    parse_date_range(&start_date, &end_date)?;
+    db.get_time_by_task(&start_date, &end_date).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_time_by_project(db: State<DbState>, start_date: String, end_date: String) -> Result<Vec<TimeTotal>, CommandError> {
+    parse_date_range(&start_date, &end_date)?;
+    db.get_time_by_project(&start_date, &end_date).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_time_by_day(db: State<DbState>, start_date: String, end_date: String) -> Result<Vec<TimeTotal>, CommandError> {
+    parse_date_range(&start_date, &end_date)?;
+    db.get_time_by_day(&start_date, &end_date).map_err(|e| e.into())
+}
+
+// Assignee commands
+#[tauri::command]
+pub fn create_assignee(db: State<DbState>, name: String, email: Option<String>) -> Result<Assignee, CommandError> {
+    db.create_assignee(&name, email.as_deref()).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_all_assignees(db: State<DbState>) -> Result<Vec<Assignee>, CommandError> {
+    db.get_all_assignees().map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn update_assignee(db: State<DbState>, id: String, name: String, email: Option<String>) -> Result<(), CommandError> {
+    db.update_assignee(&id, &name, email.as_deref()).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_assignee(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    db.delete_assignee(&id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn assign_task(
+    db: State<DbState>,
+    task_id: String,
+    assignee_id: String,
+    allocation: Option<i32>,
+) -> Result<(), CommandError> {
+    let allocation = allocation.unwrap_or(100);
+    if allocation <= 0 {
+        return Err(CommandError::new("allocation must be a positive percentage"));
+    }
+    db.assign_task(&task_id, &assignee_id, allocation).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn unassign_task(db: State<DbState>, task_id: String, assignee_id: String) -> Result<(), CommandError> {
+    db.unassign_task(&task_id, &assignee_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_task_assignments(db: State<DbState>, task_id: String) -> Result<Vec<TaskAssignment>, CommandError> {
+    db.get_task_assignments(&task_id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_workload(
+    db: State<DbState>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<AssigneeWorkload>, CommandError> {
+    let (start, end) = parse_date_range(&start_date, &end_date)?;
+
+    let assignees = db.get_all_assignees()?;
+    let assignments = db.get_assignments_between(&start_date, &end_date)?;
+    let (calendar, holidays) = db.load_calendar(None)?;
+    let work_calendar = WorkCalendar::new(&calendar, &holidays);
+    Ok(workload::compute_workload(start, end, &assignees, &assignments, &work_calendar))
+}
+
+// Calendar commands
+fn validate_work_week(work_week: &[chrono::Weekday]) -> Result<(), CommandError> {
+    if work_week.is_empty() {
+        return Err(CommandError::new("A calendar needs at least one working weekday"));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn create_calendar(
+    db: State<DbState>,
+    name: String,
+    work_week: Option<Vec<chrono::Weekday>>,
+    use_jp_holidays: Option<bool>,
+) -> Result<Calendar, CommandError> {
+    let work_week = work_week.unwrap_or_else(calendar::default_work_week);
+    validate_work_week(&work_week)?;
+    db.create_calendar(&name, &work_week, use_jp_holidays.unwrap_or(true)).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn get_calendars(db: State<DbState>) -> Result<Vec<Calendar>, CommandError> {
+    db.get_calendars().map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn update_calendar(
+    db: State<DbState>,
+    id: String,
+    name: String,
+    work_week: Vec<chrono::Weekday>,
+    use_jp_holidays: bool,
+) -> Result<(), CommandError> {
+    validate_work_week(&work_week)?;
+    db.update_calendar(&id, &name, &work_week, use_jp_holidays).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn delete_calendar(db: State<DbState>, id: String) -> Result<(), CommandError> {
+    if id == calendar::DEFAULT_CALENDAR_ID {
+        return Err(CommandError::new("The default calendar cannot be deleted"));
+    }
+    db.delete_calendar(&id).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn set_project_calendar(db: State<DbState>, project_id: String, calendar_id: Option<String>) -> Result<(), CommandError> {
+    db.set_project_calendar(&project_id, calendar_id.as_deref()).map_err(|e| e.into())
+}
+
+#[tauri::command]
+pub fn add_holiday(db: State<DbState>, calendar_id: String, date: String, name: String) -> Result<(), CommandError> {
+    NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+    db.add_holidays(&calendar_id, &[Holiday { date, name }])?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_holiday(db: State<DbState>, calendar_id: String, date: String) -> Result<(), CommandError> {
+    db.delete_holiday(&calendar_id, &date).map_err(|e| e.into())
+}
+
+// Stored holidays, plus the built-in Japanese ones for `year` when the calendar uses them
+#[tauri::command]
+pub fn get_holidays(db: State<DbState>, calendar_id: String, year: Option<i32>) -> Result<Vec<Holiday>, CommandError> {
+    let (calendar, mut holidays) = db.load_calendar(Some(&calendar_id))?;
+    if let Some(year) = year {
+        holidays.retain(|h| h.date.starts_with(&format!("{:04}-", year)));
+        if calendar.use_jp_holidays {
+            holidays.extend(calendar::jp_holidays(year).into_iter().map(|(date, name)| Holiday {
+                date: date.format("%Y-%m-%d").to_string(),
+                name: name.to_string(),
+            }));
+            holidays.sort_by(|a, b| a.date.cmp(&b.date));
         }
     }
-    
-    if !memo.is_empty() {
-        report.push_str(&format!("\n## メモ\n{}\n", memo));
+    Ok(holidays)
+}
+
+// Import holidays from an .ics or CSV ("date,name") file; returns the number imported
+#[tauri::command]
+pub fn import_holidays(db: State<DbState>, calendar_id: String, path: String) -> Result<usize, CommandError> {
+    let content = std::fs::read_to_string(&path).map_err(|e| CommandError::new(e.to_string()))?;
+    let holidays = if path.to_lowercase().ends_with(".ics") {
+        calendar::parse_ics(&content)
+    } else {
+        calendar::parse_csv(&content)
+    };
+    if holidays.is_empty() {
+        return Err(CommandError::new("No holidays found in the file"));
     }
-    
-    Ok(report)
+    db.add_holidays(&calendar_id, &holidays).map_err(|e| e.into())
+}
+
+fn project_work_calendar(db: &Database, project_id: Option<&str>) -> Result<WorkCalendar, CommandError> {
+    let (calendar, holidays) = match project_id {
+        Some(project_id) => db.load_project_calendar(project_id)?,
+        None => db.load_calendar(None)?,
+    };
+    Ok(WorkCalendar::new(&calendar, &holidays))
+}
+
+#[tauri::command]
+pub fn count_working_days(
+    db: State<DbState>,
+    project_id: Option<String>,
+    start_date: String,
+    end_date: String,
+) -> Result<i64, CommandError> {
+    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")?;
+    let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")?;
+    Ok(project_work_calendar(&db, project_id.as_deref())?.working_days_between(start, end))
+}
+
+#[tauri::command]
+pub fn add_working_days(
+    db: State<DbState>,
+    project_id: Option<String>,
+    date: String,
+    days: i64,
+) -> Result<String, CommandError> {
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+    let result = project_work_calendar(&db, project_id.as_deref())?
+        .add_working_days(date, days)
+        .ok_or_else(|| CommandError::new("Date out of range"))?;
+    Ok(result.format("%Y-%m-%d").to_string())
+}
+
+// Settings commands
+#[tauri::command]
+pub fn get_setting(db: State<DbState>, key: String) -> Result<Option<String>, CommandError> {
+    db.get_setting(&key).map_err(|e| e.into())
 }
 
+#[tauri::command]
+pub fn set_setting(db: State<DbState>, key: String, value: String) -> Result<(), CommandError> {
+    db.set_setting(&key, &value).map_err(|e| e.into())
+}
+
+// Export daily report (Markdown by default)
+#[tauri::command]
+pub fn generate_daily_report(
+    db: State<DbState>,
+    date: String,
+    memo: String,
+    format: Option<ReportFormat>,
+    group_by: Option<ReportGrouping>,
+) -> Result<String, CommandError> {
+    let todos = db.get_todos_by_date(&date)?;
+    let daily = report::build_daily_report(&date, todos, &memo, group_by.unwrap_or_default());
+    Ok(daily.render(format.unwrap_or_default()))
+}
+
+// Aggregate daily todos over a date range (weekly/monthly summaries)
+#[tauri::command]
+pub fn generate_period_report(
+    db: State<DbState>,
+    start_date: String,
+    end_date: String,
+    template: Option<String>,
+    format: Option<ReportFormat>,
+) -> Result<String, CommandError> {
+    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")?;
+    let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")?;
+    if start > end {
+        return Err(CommandError::new("start_date must not be after end_date"));
+    }
+
+    let todos = db.get_todos_between(&start_date, &end_date)?;
+    let activity = db.get_task_activity_between(&start_date, &end_date)?;
+    let milestones = db.get_milestones_between(&start_date, &end_date)?;
+    let period = report::build_period_report(start, end, &todos, &activity, milestones);
+
+    Ok(period.render(format.unwrap_or_default(), template.as_deref()))
+}
@@ -1,8 +1,14 @@
-use rusqlite::{Connection, Result, params};
-use serde::{Deserialize, Serialize};
+use rusqlite::{Connection, Result, params, params_from_iter};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Type, Value, ValueRef};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
 use std::sync::Mutex;
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
 use uuid::Uuid;
+use crate::calendar::{self, Calendar, Holiday, WorkCalendar, DEFAULT_CALENDAR_ID};
+use crate::fields::FieldType;
+use crate::recurrence::RecurrenceRule;
+use crate::todo_sync::{TodoSyncRules, SYNC_RULES_SETTING};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Project {
@@ -11,7 +17,96 @@ pub struct Project {
     pub description: Option<String>,
     pub start_date: Option<String>,
     pub end_date: Option<String>,
+    pub calendar_id: Option<String>,
     pub created_at: String,
+    // Bumped on every write; patches must name the version they were based on
+    pub version: i64,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Task,
+    // Zero-duration marker that cannot have children
+    Milestone,
+    // Dates roll up from its children
+    Summary,
+}
+
+impl TaskKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskKind::Task => "task",
+            TaskKind::Milestone => "milestone",
+            TaskKind::Summary => "summary",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "task" => Some(TaskKind::Task),
+            "milestone" => Some(TaskKind::Milestone),
+            "summary" => Some(TaskKind::Summary),
+            _ => None,
+        }
+    }
+}
+
+impl ToSql for TaskKind {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for TaskKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let kind = value.as_str()?;
+        TaskKind::parse(kind).ok_or_else(|| FromSqlError::Other(format!("unknown task kind {}", kind).into()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(TaskStatus::Pending),
+            "in_progress" => Some(TaskStatus::InProgress),
+            "completed" => Some(TaskStatus::Completed),
+            "cancelled" => Some(TaskStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+impl ToSql for TaskStatus {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for TaskStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let status = value.as_str()?;
+        TaskStatus::parse(status).ok_or_else(|| FromSqlError::Other(format!("unknown task status {}", status).into()))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,13 +116,20 @@ pub struct Task {
     pub parent_id: Option<String>,
     pub title: String,
     pub description: Option<String>,
-    pub status: String,
+    pub status: TaskStatus,
     pub priority: i32,
     pub start_date: Option<String>,
     pub end_date: Option<String>,
     pub progress: i32,
     pub order_index: i32,
+    pub estimate_hours: Option<f64>,
+    pub kind: TaskKind,
+    // Progress follows the checklist completion ratio
+    pub progress_from_checklist: bool,
     pub created_at: String,
+    // Bumped on every write; patches must name the version they were based on
+    pub version: i64,
+    pub updated_at: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -38,7 +140,17 @@ pub struct DailyTodo {
     pub date: String,
     pub completed: bool,
     pub memo: Option<String>,
+    pub carry_count: i32,
+    pub carried_from: Option<String>,
+    pub order_index: i32,
+    pub priority: i32,
+    // "HH:MM" start of the time box, with its length in minutes
+    pub scheduled_time: Option<String>,
+    pub duration_minutes: Option<i32>,
+    // Completing this todo finishes the linked task
+    pub is_final: bool,
     pub created_at: String,
+    pub updated_at: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -49,338 +161,4178 @@ pub struct DailyTodoWithTask {
     pub date: String,
     pub completed: bool,
     pub memo: Option<String>,
+    pub carry_count: i32,
+    pub carried_from: Option<String>,
+    pub order_index: i32,
+    pub priority: i32,
+    // "HH:MM" start of the time box, with its length in minutes
+    pub scheduled_time: Option<String>,
+    pub duration_minutes: Option<i32>,
+    pub is_final: bool,
     pub created_at: String,
+    pub updated_at: String,
     pub task_title: Option<String>,
+    pub task_status: Option<TaskStatus>,
+    pub task_progress: Option<i32>,
+    pub parent_task_id: Option<String>,
+    pub parent_task_title: Option<String>,
     pub project_name: Option<String>,
+    pub tracked_seconds: i64,
+    // Names of the todo's own tags and its linked task's tags
+    pub tags: Vec<String>,
 }
 
-pub struct Database {
-    pub conn: Mutex<Connection>,
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskActivity {
+    pub id: String,
+    pub task_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: String,
+    pub task_title: String,
+    pub project_name: String,
 }
 
-impl Database {
-    pub fn new(db_path: &str) -> Result<Self> {
-        let conn = Connection::open(db_path)?;
-        let db = Database {
-            conn: Mutex::new(conn),
-        };
-        db.init_schema()?;
-        Ok(db)
-    }
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskComment {
+    pub id: String,
+    pub task_id: String,
+    // Comment this one replies to
+    pub parent_id: Option<String>,
+    pub author: Option<String>,
+    pub body: String,
+    pub created_at: String,
+    pub edited_at: Option<String>,
+}
 
-    fn init_schema(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS projects (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                description TEXT,
-                start_date TEXT,
-                end_date TEXT,
-                created_at TEXT NOT NULL
-            )",
-            [],
-        )?;
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecklistItem {
+    pub id: String,
+    pub task_id: String,
+    pub title: String,
+    pub checked: bool,
+    pub order_index: i32,
+    pub created_at: String,
+}
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS tasks (
-                id TEXT PRIMARY KEY,
-                project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
-                parent_id TEXT REFERENCES tasks(id) ON DELETE CASCADE,
-                title TEXT NOT NULL,
-                description TEXT,
-                status TEXT DEFAULT 'pending',
-                priority INTEGER DEFAULT 0,
-                start_date TEXT,
-                end_date TEXT,
-                progress INTEGER DEFAULT 0,
-                order_index INTEGER DEFAULT 0,
-                created_at TEXT NOT NULL
-            )",
-            [],
-        )?;
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Attachment {
+    pub id: String,
+    pub task_id: String,
+    pub name: String,
+    pub size: i64,
+    pub mime: String,
+    // SHA-256 of the content, also its file name in the attachment store
+    pub hash: String,
+    pub created_at: String,
+}
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS daily_todos (
-                id TEXT PRIMARY KEY,
-                task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
-                title TEXT NOT NULL,
-                date TEXT NOT NULL,
-                completed INTEGER DEFAULT 0,
-                memo TEXT,
-                created_at TEXT NOT NULL
-            )",
-            [],
-        )?;
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecurringTodo {
+    pub id: String,
+    pub task_id: Option<String>,
+    pub title: String,
+    pub memo: Option<String>,
+    pub rule: RecurrenceRule,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub created_at: String,
+}
 
-        // Create indexes for better performance
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks(project_id)",
-            [],
-        )?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id)",
-            [],
-        )?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_daily_todos_date ON daily_todos(date)",
-            [],
-        )?;
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeEntry {
+    pub id: String,
+    pub todo_id: Option<String>,
+    pub task_id: Option<String>,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub duration_seconds: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
 
-        Ok(())
-    }
+// Tracked time aggregated per task, project or day
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeTotal {
+    pub key: Option<String>,
+    pub label: Option<String>,
+    pub seconds: i64,
+}
 
-    // Project CRUD operations
-    pub fn create_project(&self, name: &str, description: Option<&str>, start_date: Option<&str>, end_date: Option<&str>) -> Result<Project> {
-        let conn = self.conn.lock().unwrap();
-        let id = Uuid::new_v4().to_string();
-        let created_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Assignee {
+    pub id: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub created_at: String,
+}
 
-        conn.execute(
-            "INSERT INTO projects (id, name, description, start_date, end_date, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![id, name, description, start_date, end_date, created_at],
-        )?;
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskAssignment {
+    pub task_id: String,
+    pub assignee_id: String,
+    pub assignee_name: String,
+    pub allocation: i32,
+    pub task_title: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
 
-        Ok(Project {
-            id,
-            name: name.to_string(),
-            description: description.map(|s| s.to_string()),
-            start_date: start_date.map(|s| s.to_string()),
-            end_date: end_date.map(|s| s.to_string()),
-            created_at,
-        })
-    }
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Milestone {
+    pub id: String,
+    pub project_id: String,
+    pub project_name: String,
+    pub title: String,
+    pub date: String,
+    pub status: TaskStatus,
+    pub progress: i32,
+}
 
-    pub fn get_all_projects(&self) -> Result<Vec<Project>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT id, name, description, start_date, end_date, created_at FROM projects ORDER BY created_at DESC")?;
-        
-        let projects = stmt.query_map([], |row| {
-            Ok(Project {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                start_date: row.get(3)?,
-                end_date: row.get(4)?,
-                created_at: row.get(5)?,
-            })
-        })?.collect::<Result<Vec<_>>>()?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSort {
+    #[default]
+    OrderIndex,
+    Priority,
+    StartDate,
+    EndDate,
+    Title,
+    CreatedAt,
+}
 
-        Ok(projects)
+// Filters for querying tasks across projects; unset fields don't filter
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct TaskQuery {
+    pub project_id: Option<String>,
+    pub statuses: Vec<TaskStatus>,
+    pub kinds: Vec<TaskKind>,
+    pub priority_min: Option<i32>,
+    pub priority_max: Option<i32>,
+    // Tasks whose start..end span overlaps this inclusive window
+    pub window_start: Option<String>,
+    pub window_end: Option<String>,
+    // Past their end date and neither completed nor cancelled
+    pub overdue: bool,
+    // Open tasks ending between today and this many days ahead; relative so saved views stay current
+    pub due_within_days: Option<i64>,
+    pub assignee_id: Option<String>,
+    pub tag_id: Option<String>,
+    // Custom field conditions, all of which must hold
+    pub fields: Vec<FieldFilter>,
+    // Substring of the title or description
+    pub text: Option<String>,
+    pub sort: TaskSort,
+    pub descending: bool,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub color: String,
+    pub created_at: String,
+}
+
+// A tag attached to a task or todo
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TagLink {
+    pub item_id: String,
+    pub tag_id: String,
+}
+
+// Field definition shared by the tasks of one project
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomField {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub field_type: FieldType,
+    // Allowed values for enum fields
+    pub options: Vec<String>,
+    pub order_index: i32,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskFieldValue {
+    pub task_id: String,
+    pub field_id: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldOp {
+    Eq,
+    Contains,
+    // Numeric for number fields, otherwise by text (which orders dates correctly)
+    Gte,
+    Lte,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldFilter {
+    pub field_id: String,
+    pub op: FieldOp,
+    pub value: String,
+}
+
+// A named task query kept in the database
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedView {
+    pub id: String,
+    pub name: String,
+    pub query: TaskQuery,
+    pub order_index: i32,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskPage {
+    pub tasks: Vec<Task>,
+    // Matches before pagination
+    pub total: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SearchFilters {
+    // "project", "task" and/or "todo"; all kinds when empty
+    #[serde(default)]
+    pub kinds: Vec<String>,
+    pub project_id: Option<String>,
+    // Inclusive range on the todo date, or the creation date for projects and tasks
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchHit {
+    pub kind: String,
+    pub id: String,
+    pub title: String,
+    // Matching text with hits wrapped in 【】
+    pub snippet: String,
+    // Lower is more relevant
+    pub rank: f64,
+    pub project_id: Option<String>,
+    pub project_name: Option<String>,
+    pub task_id: Option<String>,
+    pub task_title: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Baseline {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+// A task's dates and progress as captured in a baseline
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BaselineTask {
+    pub baseline_id: String,
+    pub task_id: String,
+    pub title: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub progress: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CarryOverMode {
+    // Reschedule the original todo
+    Move,
+    // Leave the original in place and add a linked copy
+    Copy,
+}
+
+// Outcome for one id of a bulk command
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BulkItemResult {
+    pub id: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+// Why a single item of a bulk operation failed
+enum BulkError {
+    Db(rusqlite::Error),
+    Rejected(String),
+}
+
+impl From<rusqlite::Error> for BulkError {
+    fn from(err: rusqlite::Error) -> Self {
+        BulkError::Db(err)
     }
+}
 
-    pub fn get_project(&self, id: &str) -> Result<Option<Project>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT id, name, description, start_date, end_date, created_at FROM projects WHERE id = ?1")?;
-        
-        let mut rows = stmt.query(params![id])?;
-        if let Some(row) = rows.next()? {
-            Ok(Some(Project {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                start_date: row.get(3)?,
-                end_date: row.get(4)?,
+// Fields to change in a patch; a missing key keeps the current value, null clears it
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProjectPatch {
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub start_date: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub end_date: Option<Option<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TaskPatch {
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub description: Option<Option<String>>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<i32>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub start_date: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub end_date: Option<Option<String>>,
+    pub progress: Option<i32>,
+}
+
+// Result of a patch checked against the version the caller last saw
+#[derive(Debug)]
+pub enum PatchOutcome<T> {
+    Applied(T),
+    // Someone else wrote in between; carries the current row
+    Stale(T),
+    NotFound,
+}
+
+// A project, task or todo removed after the tombstone's time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeletedRecord {
+    // "project", "task" or "todo"
+    pub kind: String,
+    pub id: String,
+    pub deleted_at: String,
+}
+
+// Rows written since a point in time; `until` is the `since` to pass on the next call
+#[derive(Debug, Serialize)]
+pub struct ChangeSet {
+    pub since: String,
+    pub until: String,
+    pub projects: Vec<Project>,
+    pub tasks: Vec<Task>,
+    pub todos: Vec<DailyTodoWithTask>,
+    pub deleted: Vec<DeletedRecord>,
+}
+
+fn double_option<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+const PROJECT_COLUMNS: &str = "id, name, description, start_date, end_date, calendar_id, created_at, version, updated_at";
+
+fn project_from_row(row: &rusqlite::Row) -> Result<Project> {
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        start_date: row.get(3)?,
+        end_date: row.get(4)?,
+        calendar_id: row.get(5)?,
+        created_at: row.get(6)?,
+        version: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+const TASK_COLUMNS: &str =
+    "id, project_id, parent_id, title, description, status, priority, start_date, end_date, progress, order_index, estimate_hours, kind, progress_from_checklist, created_at, version, updated_at";
+
+fn task_from_row(row: &rusqlite::Row) -> Result<Task> {
+    Ok(Task {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        parent_id: row.get(2)?,
+        title: row.get(3)?,
+        description: row.get(4)?,
+        status: row.get(5)?,
+        priority: row.get(6)?,
+        start_date: row.get(7)?,
+        end_date: row.get(8)?,
+        progress: row.get(9)?,
+        order_index: row.get(10)?,
+        estimate_hours: row.get(11)?,
+        kind: row.get(12)?,
+        progress_from_checklist: row.get(13)?,
+        created_at: row.get(14)?,
+        version: row.get(15)?,
+        updated_at: row.get(16)?,
+    })
+}
+
+// Daily todos joined with their linked task, its WBS parent and project
+const TODO_WITH_TASK_SELECT: &str =
+    "SELECT dt.id, dt.task_id, dt.title, dt.date, dt.completed, dt.memo, dt.carry_count, dt.carried_from, dt.created_at,
+            t.title as task_title, t.status as task_status, t.progress as task_progress,
+            parent.id as parent_task_id, parent.title as parent_task_title, p.name as project_name,
+            (SELECT COALESCE(SUM(te.duration_seconds), 0) FROM time_entries te WHERE te.todo_id = dt.id) as tracked_seconds,
+            (SELECT GROUP_CONCAT(name, char(31)) FROM (
+                SELECT tg.name FROM todo_tags tt JOIN tags tg ON tg.id = tt.tag_id WHERE tt.todo_id = dt.id
+                UNION
+                SELECT tg.name FROM task_tags tk JOIN tags tg ON tg.id = tk.tag_id WHERE tk.task_id = dt.task_id
+            )) as tags,
+            dt.order_index, dt.priority, dt.scheduled_time, dt.duration_minutes, dt.is_final, dt.updated_at
+     FROM daily_todos dt
+     LEFT JOIN tasks t ON dt.task_id = t.id
+     LEFT JOIN tasks parent ON t.parent_id = parent.id
+     LEFT JOIN projects p ON t.project_id = p.id";
+
+fn todo_with_task_from_row(row: &rusqlite::Row) -> Result<DailyTodoWithTask> {
+    Ok(DailyTodoWithTask {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        title: row.get(2)?,
+        date: row.get(3)?,
+        completed: row.get::<_, i32>(4)? == 1,
+        memo: row.get(5)?,
+        carry_count: row.get(6)?,
+        carried_from: row.get(7)?,
+        created_at: row.get(8)?,
+        task_title: row.get(9)?,
+        task_status: row.get(10)?,
+        task_progress: row.get(11)?,
+        parent_task_id: row.get(12)?,
+        parent_task_title: row.get(13)?,
+        project_name: row.get(14)?,
+        tracked_seconds: row.get(15)?,
+        tags: row
+            .get::<_, Option<String>>(16)?
+            .map(|names| names.split('\u{1f}').map(|n| n.to_string()).collect())
+            .unwrap_or_default(),
+        order_index: row.get(17)?,
+        priority: row.get(18)?,
+        scheduled_time: row.get(19)?,
+        duration_minutes: row.get(20)?,
+        is_final: row.get::<_, i32>(21)? == 1,
+        updated_at: row.get(22)?,
+    })
+}
+
+// Position after the last todo of `date`, so new and carried items land at the end of the day's list
+fn next_todo_order(conn: &Connection, date: &str) -> Result<i32> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(order_index), -1) + 1 FROM daily_todos WHERE date = ?1",
+        params![date],
+        |row| row.get(0),
+    )
+}
+
+const TIME_ENTRY_COLUMNS: &str = "id, todo_id, task_id, started_at, ended_at, duration_seconds, note, created_at";
+
+fn time_entry_from_row(row: &rusqlite::Row) -> Result<TimeEntry> {
+    Ok(TimeEntry {
+        id: row.get(0)?,
+        todo_id: row.get(1)?,
+        task_id: row.get(2)?,
+        started_at: row.get(3)?,
+        ended_at: row.get(4)?,
+        duration_seconds: row.get(5)?,
+        note: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+fn seconds_between(started_at: &str, ended_at: &str) -> i64 {
+    match (DateTime::parse_from_rfc3339(started_at), DateTime::parse_from_rfc3339(ended_at)) {
+        (Ok(start), Ok(end)) => (end - start).num_seconds().max(0),
+        _ => 0,
+    }
+}
+
+fn attachment_from_row(row: &rusqlite::Row) -> Result<Attachment> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        name: row.get(2)?,
+        size: row.get(3)?,
+        mime: row.get(4)?,
+        hash: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn task_comment_from_row(row: &rusqlite::Row) -> Result<TaskComment> {
+    Ok(TaskComment {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        parent_id: row.get(2)?,
+        author: row.get(3)?,
+        body: row.get(4)?,
+        created_at: row.get(5)?,
+        edited_at: row.get(6)?,
+    })
+}
+
+fn recurring_todo_from_row(row: &rusqlite::Row) -> Result<RecurringTodo> {
+    let rule: String = row.get(4)?;
+    Ok(RecurringTodo {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        title: row.get(2)?,
+        memo: row.get(3)?,
+        rule: serde_json::from_str(&rule)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(4, Type::Text, Box::new(e)))?,
+        start_date: row.get(5)?,
+        end_date: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+fn rule_to_json(rule: &RecurrenceRule) -> Result<String> {
+    serde_json::to_string(rule).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn custom_field_from_row(row: &rusqlite::Row) -> Result<CustomField> {
+    let field_type: String = row.get(3)?;
+    let options: String = row.get(4)?;
+    Ok(CustomField {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        field_type: FieldType::parse(&field_type).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(3, Type::Text, format!("unknown field type {}", field_type).into())
+        })?,
+        options: serde_json::from_str(&options)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(4, Type::Text, Box::new(e)))?,
+        order_index: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn options_to_json(options: &[String]) -> Result<String> {
+    serde_json::to_string(options).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn saved_view_from_row(row: &rusqlite::Row) -> Result<SavedView> {
+    let query: String = row.get(2)?;
+    Ok(SavedView {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        query: serde_json::from_str(&query)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, Type::Text, Box::new(e)))?,
+        order_index: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+fn task_query_to_json(query: &TaskQuery) -> Result<String> {
+    serde_json::to_string(query).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn calendar_from_row(row: &rusqlite::Row) -> Result<Calendar> {
+    let work_week: String = row.get(2)?;
+    Ok(Calendar {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        work_week: serde_json::from_str(&work_week)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, Type::Text, Box::new(e)))?,
+        use_jp_holidays: row.get::<_, i32>(3)? == 1,
+        created_at: row.get(4)?,
+    })
+}
+
+fn work_week_to_json(work_week: &[chrono::Weekday]) -> Result<String> {
+    serde_json::to_string(work_week).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+const SEARCH_DEFAULT_LIMIT: i64 = 50;
+
+// The trigram tokenizer cannot match terms shorter than this; they fall back to LIKE
+const TRIGRAM_MIN_CHARS: usize = 3;
+
+// Keeps search_index in step with the searchable columns
+const SEARCH_TRIGGERS: &str = "
+    CREATE TRIGGER IF NOT EXISTS projects_search_insert AFTER INSERT ON projects BEGIN
+        INSERT INTO search_index (kind, ref_id, title, body) VALUES ('project', new.id, new.name, COALESCE(new.description, ''));
+    END;
+    CREATE TRIGGER IF NOT EXISTS projects_search_update AFTER UPDATE OF name, description ON projects BEGIN
+        UPDATE search_index SET title = new.name, body = COALESCE(new.description, '') WHERE kind = 'project' AND ref_id = new.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS projects_search_delete AFTER DELETE ON projects BEGIN
+        DELETE FROM search_index WHERE kind = 'project' AND ref_id = old.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS tasks_search_insert AFTER INSERT ON tasks BEGIN
+        INSERT INTO search_index (kind, ref_id, title, body) VALUES ('task', new.id, new.title, COALESCE(new.description, ''));
+    END;
+    CREATE TRIGGER IF NOT EXISTS tasks_search_update AFTER UPDATE OF title, description ON tasks BEGIN
+        UPDATE search_index SET title = new.title, body = COALESCE(new.description, '') WHERE kind = 'task' AND ref_id = new.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS tasks_search_delete AFTER DELETE ON tasks BEGIN
+        DELETE FROM search_index WHERE kind = 'task' AND ref_id = old.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS daily_todos_search_insert AFTER INSERT ON daily_todos BEGIN
+        INSERT INTO search_index (kind, ref_id, title, body) VALUES ('todo', new.id, new.title, COALESCE(new.memo, ''));
+    END;
+    CREATE TRIGGER IF NOT EXISTS daily_todos_search_update AFTER UPDATE OF title, memo ON daily_todos BEGIN
+        UPDATE search_index SET title = new.title, body = COALESCE(new.memo, '') WHERE kind = 'todo' AND ref_id = new.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS daily_todos_search_delete AFTER DELETE ON daily_todos BEGIN
+        DELETE FROM search_index WHERE kind = 'todo' AND ref_id = old.id;
+    END;";
+
+// Any UPDATE that leaves `version` alone bumps it and stamps `updated_at`, so every write path
+// invalidates older patches and shows up in get_changes_since; deletes leave a tombstone
+const VERSION_TRIGGERS: &str = "
+    CREATE TRIGGER IF NOT EXISTS projects_version AFTER UPDATE ON projects WHEN new.version = old.version BEGIN
+        UPDATE projects SET version = old.version + 1, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = new.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS tasks_version AFTER UPDATE ON tasks WHEN new.version = old.version BEGIN
+        UPDATE tasks SET version = old.version + 1, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = new.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS daily_todos_touch AFTER UPDATE ON daily_todos WHEN new.updated_at IS old.updated_at BEGIN
+        UPDATE daily_todos SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = new.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS projects_tombstone AFTER DELETE ON projects BEGIN
+        INSERT OR REPLACE INTO deleted_records (kind, id, deleted_at) VALUES ('project', old.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    END;
+    CREATE TRIGGER IF NOT EXISTS tasks_tombstone AFTER DELETE ON tasks BEGIN
+        INSERT OR REPLACE INTO deleted_records (kind, id, deleted_at) VALUES ('task', old.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    END;
+    CREATE TRIGGER IF NOT EXISTS daily_todos_tombstone AFTER DELETE ON daily_todos BEGIN
+        INSERT OR REPLACE INTO deleted_records (kind, id, deleted_at) VALUES ('todo', old.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    END;";
+
+// Task sets for delete_tasks_in: a task with all its descendants, and every task of a project
+const TASK_SUBTREE: &str =
+    "WITH RECURSIVE target(id) AS (SELECT ?1 UNION ALL SELECT t.id FROM tasks t JOIN target ON t.parent_id = target.id)";
+const PROJECT_TASKS: &str = "WITH target(id) AS (SELECT id FROM tasks WHERE project_id = ?1)";
+
+// Rows removed with their task (ON DELETE CASCADE) and rows that only lose the link (ON DELETE SET NULL)
+const TASK_OWNED_TABLES: [&str; 7] = [
+    "task_assignees",
+    "task_field_values",
+    "task_tags",
+    "task_activity",
+    "task_comments",
+    "checklist_items",
+    "attachments",
+];
+const TASK_LINKED_TABLES: [&str; 3] = ["daily_todos", "recurring_todos", "time_entries"];
+
+// Timestamp columns rewritten from the old local "YYYY-MM-DD HH:MM:SS" format to UTC ISO-8601
+const TIMESTAMP_COLUMNS: &[(&str, &str)] = &[
+    ("projects", "created_at"),
+    ("tasks", "created_at"),
+    ("daily_todos", "created_at"),
+    ("recurring_todos", "created_at"),
+    ("time_entries", "created_at"),
+    ("time_entries", "started_at"),
+    ("time_entries", "ended_at"),
+    ("assignees", "created_at"),
+    ("calendars", "created_at"),
+    ("baselines", "created_at"),
+    ("task_activity", "changed_at"),
+    ("task_comments", "created_at"),
+    ("task_comments", "edited_at"),
+    ("checklist_items", "created_at"),
+    ("attachments", "created_at"),
+    ("tags", "created_at"),
+    ("custom_fields", "created_at"),
+    ("saved_views", "created_at"),
+];
+
+// Hits joined with their project and task context
+const SEARCH_SELECT: &str =
+    "SELECT s.kind, s.ref_id, s.title, s.body, {rank}, {snippet},
+            COALESCE(p.id, tp.id), COALESCE(p.name, tp.name), COALESCE(t.id, dt.id), COALESCE(t.title, dt.title),
+            d.date, COALESCE(d.date, date(t.created_at, 'localtime'), date(p.created_at, 'localtime')) as hit_date
+     FROM search_index s
+     LEFT JOIN projects p ON s.kind = 'project' AND p.id = s.ref_id
+     LEFT JOIN tasks t ON s.kind = 'task' AND t.id = s.ref_id
+     LEFT JOIN daily_todos d ON s.kind = 'todo' AND d.id = s.ref_id
+     LEFT JOIN tasks dt ON dt.id = d.task_id
+     LEFT JOIN projects tp ON tp.id = COALESCE(t.project_id, dt.project_id)";
+
+// WHERE clause and parameters for a task query
+fn task_query_conditions(query: &TaskQuery, today: NaiveDate) -> (String, Vec<Value>) {
+    let mut conditions = vec!["1 = 1".to_string()];
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(ref project_id) = query.project_id {
+        conditions.push("project_id = ?".to_string());
+        values.push(Value::Text(project_id.clone()));
+    }
+    if !query.statuses.is_empty() {
+        conditions.push(format!("status IN ({})", vec!["?"; query.statuses.len()].join(", ")));
+        values.extend(query.statuses.iter().map(|status| Value::Text(status.as_str().to_string())));
+    }
+    if !query.kinds.is_empty() {
+        conditions.push(format!("kind IN ({})", vec!["?"; query.kinds.len()].join(", ")));
+        values.extend(query.kinds.iter().map(|kind| Value::Text(kind.as_str().to_string())));
+    }
+    if let Some(min) = query.priority_min {
+        conditions.push("priority >= ?".to_string());
+        values.push(Value::Integer(min.into()));
+    }
+    if let Some(max) = query.priority_max {
+        conditions.push("priority <= ?".to_string());
+        values.push(Value::Integer(max.into()));
+    }
+    if let Some(ref window_end) = query.window_end {
+        conditions.push("COALESCE(start_date, end_date) <= ?".to_string());
+        values.push(Value::Text(window_end.clone()));
+    }
+    if let Some(ref window_start) = query.window_start {
+        conditions.push("COALESCE(end_date, start_date) >= ?".to_string());
+        values.push(Value::Text(window_start.clone()));
+    }
+    if query.overdue {
+        conditions.push("end_date < ? AND status NOT IN ('completed', 'cancelled')".to_string());
+        values.push(Value::Text(today.format("%Y-%m-%d").to_string()));
+    }
+    if let Some(days) = query.due_within_days {
+        conditions.push("end_date BETWEEN ? AND ? AND status NOT IN ('completed', 'cancelled')".to_string());
+        values.push(Value::Text(today.format("%Y-%m-%d").to_string()));
+        // Views saved before validation may hold any value; capping keeps the date in four-digit years
+        let until = today + chrono::Duration::days(days.clamp(0, 36500));
+        values.push(Value::Text(until.format("%Y-%m-%d").to_string()));
+    }
+    if let Some(ref assignee_id) = query.assignee_id {
+        conditions.push("EXISTS (SELECT 1 FROM task_assignees ta WHERE ta.task_id = tasks.id AND ta.assignee_id = ?)".to_string());
+        values.push(Value::Text(assignee_id.clone()));
+    }
+    if let Some(ref tag_id) = query.tag_id {
+        conditions.push("EXISTS (SELECT 1 FROM task_tags tk WHERE tk.task_id = tasks.id AND tk.tag_id = ?)".to_string());
+        values.push(Value::Text(tag_id.clone()));
+    }
+    for filter in &query.fields {
+        let condition = match filter.op {
+            FieldOp::Eq => "CASE WHEN f.field_type = 'number' THEN CAST(v.value AS REAL) = CAST(?v AS REAL) ELSE v.value = ?v END",
+            FieldOp::Contains => "v.value LIKE '%' || ?v || '%'",
+            FieldOp::Gte => "CASE WHEN f.field_type = 'number' THEN CAST(v.value AS REAL) >= CAST(?v AS REAL) ELSE v.value >= ?v END",
+            FieldOp::Lte => "CASE WHEN f.field_type = 'number' THEN CAST(v.value AS REAL) <= CAST(?v AS REAL) ELSE v.value <= ?v END",
+        };
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM task_field_values v JOIN custom_fields f ON f.id = v.field_id
+                     WHERE v.task_id = tasks.id AND v.field_id = ? AND {})",
+            condition.replace("?v", "?")
+        ));
+        values.push(Value::Text(filter.field_id.clone()));
+        for _ in 0..condition.matches("?v").count() {
+            values.push(Value::Text(filter.value.clone()));
+        }
+    }
+    if let Some(text) = query.text.as_deref().filter(|t| !t.trim().is_empty()) {
+        conditions.push("(title LIKE ? OR description LIKE ?)".to_string());
+        let pattern = format!("%{}%", text.trim());
+        values.push(Value::Text(pattern.clone()));
+        values.push(Value::Text(pattern));
+    }
+
+    (conditions.join(" AND "), values)
+}
+
+fn task_sort_clause(sort: TaskSort, descending: bool) -> String {
+    let column = match sort {
+        TaskSort::OrderIndex => "order_index",
+        TaskSort::Priority => "priority",
+        TaskSort::StartDate => "start_date",
+        TaskSort::EndDate => "end_date",
+        TaskSort::Title => "title",
+        TaskSort::CreatedAt => "created_at",
+    };
+    let direction = if descending { "DESC" } else { "ASC" };
+    // Undated tasks go last either way
+    format!("{} IS NULL, {} {}, project_id, order_index", column, column, direction)
+}
+
+// Text around the first occurrence of `term`, for hits found without FTS ranking
+fn like_snippet(text: &str, term: &str) -> Option<String> {
+    const CONTEXT_CHARS: usize = 20;
+    let lower = text.to_lowercase();
+    let term = term.to_lowercase();
+    // Lowercasing can change byte lengths; only use the position when it maps back cleanly
+    let byte_pos = lower.find(&term).filter(|_| lower.len() == text.len())?;
+    let start_char = text[..byte_pos].chars().count();
+    let term_chars = term.chars().count();
+    let chars: Vec<char> = text.chars().collect();
+    let from = start_char.saturating_sub(CONTEXT_CHARS);
+    let to = (start_char + term_chars + CONTEXT_CHARS).min(chars.len());
+    Some(format!(
+        "{}{}【{}】{}{}",
+        if from > 0 { "…" } else { "" },
+        chars[from..start_char].iter().collect::<String>(),
+        chars[start_char..start_char + term_chars].iter().collect::<String>(),
+        chars[start_char + term_chars..to].iter().collect::<String>(),
+        if to < chars.len() { "…" } else { "" },
+    ))
+}
+
+// Adds a column to tables created by older versions of the app
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?
+        .exists(params![column])?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+// Rewrites legacy local timestamps to UTC; rows already ending in "Z" are left alone
+fn migrate_timestamps(conn: &Connection) -> Result<()> {
+    for (table, column) in TIMESTAMP_COLUMNS {
+        conn.execute(
+            &format!(
+                "UPDATE {table} SET {column} = strftime('%Y-%m-%dT%H:%M:%fZ', {column}, 'utc') WHERE {column} NOT LIKE '%Z' AND julianday({column}) IS NOT NULL"
+            ),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+// Stored timestamp form: UTC ISO-8601 with milliseconds, e.g. "2024-05-01T03:04:05.678Z"; sorts as text
+pub fn utc_timestamp<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+    time.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+pub fn now_timestamp() -> String {
+    utc_timestamp(&Utc::now())
+}
+
+// Optional "YYYY-MM-DD" column value; None when missing or malformed
+pub(crate) fn parse_date(date: &Option<String>) -> Option<NaiveDate> {
+    date.as_deref().and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+}
+
+pub struct Database {
+    pub conn: Mutex<Connection>,
+}
+
+impl Database {
+    pub fn new(db_path: &str) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
+        let db = Database {
+            conn: Mutex::new(conn),
+        };
+        db.init_schema()?;
+        Ok(db)
+    }
+
+    fn init_schema(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS projects (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                start_date TEXT,
+                end_date TEXT,
+                calendar_id TEXT REFERENCES calendars(id) ON DELETE SET NULL,
+                created_at TEXT NOT NULL,
+                version INTEGER NOT NULL DEFAULT 1,
+                updated_at TEXT
+            )",
+            [],
+        )?;
+        add_column_if_missing(&conn, "projects", "calendar_id", "TEXT REFERENCES calendars(id) ON DELETE SET NULL")?;
+        add_column_if_missing(&conn, "projects", "version", "INTEGER NOT NULL DEFAULT 1")?;
+        add_column_if_missing(&conn, "projects", "updated_at", "TEXT")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tasks (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+                parent_id TEXT REFERENCES tasks(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                status TEXT DEFAULT 'pending',
+                priority INTEGER DEFAULT 0,
+                start_date TEXT,
+                end_date TEXT,
+                progress INTEGER DEFAULT 0,
+                order_index INTEGER DEFAULT 0,
+                estimate_hours REAL,
+                kind TEXT NOT NULL DEFAULT 'task',
+                progress_from_checklist INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                version INTEGER NOT NULL DEFAULT 1,
+                updated_at TEXT
+            )",
+            [],
+        )?;
+        add_column_if_missing(&conn, "tasks", "estimate_hours", "REAL")?;
+        add_column_if_missing(&conn, "tasks", "kind", "TEXT NOT NULL DEFAULT 'task'")?;
+        add_column_if_missing(&conn, "tasks", "progress_from_checklist", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(&conn, "tasks", "version", "INTEGER NOT NULL DEFAULT 1")?;
+        add_column_if_missing(&conn, "tasks", "updated_at", "TEXT")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS daily_todos (
+                id TEXT PRIMARY KEY,
+                task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+                title TEXT NOT NULL,
+                date TEXT NOT NULL,
+                completed INTEGER DEFAULT 0,
+                memo TEXT,
+                carry_count INTEGER DEFAULT 0,
+                carried_from TEXT REFERENCES daily_todos(id) ON DELETE SET NULL,
+                order_index INTEGER DEFAULT 0,
+                priority INTEGER DEFAULT 0,
+                scheduled_time TEXT,
+                duration_minutes INTEGER,
+                is_final INTEGER DEFAULT 0,
+                created_at TEXT NOT NULL,
+                updated_at TEXT
+            )",
+            [],
+        )?;
+        add_column_if_missing(&conn, "daily_todos", "carry_count", "INTEGER DEFAULT 0")?;
+        add_column_if_missing(&conn, "daily_todos", "carried_from", "TEXT REFERENCES daily_todos(id) ON DELETE SET NULL")?;
+        add_column_if_missing(&conn, "daily_todos", "order_index", "INTEGER DEFAULT 0")?;
+        add_column_if_missing(&conn, "daily_todos", "priority", "INTEGER DEFAULT 0")?;
+        add_column_if_missing(&conn, "daily_todos", "scheduled_time", "TEXT")?;
+        add_column_if_missing(&conn, "daily_todos", "duration_minutes", "INTEGER")?;
+        add_column_if_missing(&conn, "daily_todos", "is_final", "INTEGER DEFAULT 0")?;
+        add_column_if_missing(&conn, "daily_todos", "updated_at", "TEXT")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS recurring_todos (
+                id TEXT PRIMARY KEY,
+                task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+                title TEXT NOT NULL,
+                memo TEXT,
+                rule TEXT NOT NULL,
+                start_date TEXT NOT NULL,
+                end_date TEXT,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // One row per rule and date that has been materialized, so deleted instances stay deleted
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS recurring_todo_instances (
+                recurring_id TEXT NOT NULL REFERENCES recurring_todos(id) ON DELETE CASCADE,
+                date TEXT NOT NULL,
+                todo_id TEXT,
+                PRIMARY KEY (recurring_id, date)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS time_entries (
+                id TEXT PRIMARY KEY,
+                todo_id TEXT REFERENCES daily_todos(id) ON DELETE SET NULL,
+                task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                duration_seconds INTEGER,
+                note TEXT,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS assignees (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                email TEXT,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // allocation is the percentage of the assignee's day spent on the task
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS task_assignees (
+                task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                assignee_id TEXT NOT NULL REFERENCES assignees(id) ON DELETE CASCADE,
+                allocation INTEGER NOT NULL DEFAULT 100,
+                PRIMARY KEY (task_id, assignee_id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS calendars (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                work_week TEXT NOT NULL,
+                use_jp_holidays INTEGER DEFAULT 0,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS calendar_holidays (
+                calendar_id TEXT NOT NULL REFERENCES calendars(id) ON DELETE CASCADE,
+                date TEXT NOT NULL,
+                name TEXT NOT NULL,
+                PRIMARY KEY (calendar_id, date)
+            )",
+            [],
+        )?;
+
+        // Built-in calendar: Monday to Friday with Japanese public holidays
+        conn.execute(
+            "INSERT OR IGNORE INTO calendars (id, name, work_week, use_jp_holidays, created_at) VALUES (?1, ?2, ?3, 1, ?4)",
+            params![
+                DEFAULT_CALENDAR_ID,
+                "標準（土日・祝日休み）",
+                work_week_to_json(&calendar::default_work_week())?,
+                now_timestamp()
+            ],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS baselines (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // No foreign key to tasks: deleted tasks stay in the snapshot
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS baseline_tasks (
+                baseline_id TEXT NOT NULL REFERENCES baselines(id) ON DELETE CASCADE,
+                task_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                start_date TEXT,
+                end_date TEXT,
+                progress INTEGER DEFAULT 0,
+                PRIMARY KEY (baseline_id, task_id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS custom_fields (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT NOT NULL DEFAULT '[]',
+                order_index INTEGER DEFAULT 0,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS task_field_values (
+                task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                field_id TEXT NOT NULL REFERENCES custom_fields(id) ON DELETE CASCADE,
+                value TEXT NOT NULL,
+                PRIMARY KEY (task_id, field_id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tags (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                color TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS task_tags (
+                task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                tag_id TEXT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+                PRIMARY KEY (task_id, tag_id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS todo_tags (
+                todo_id TEXT NOT NULL REFERENCES daily_todos(id) ON DELETE CASCADE,
+                tag_id TEXT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+                PRIMARY KEY (todo_id, tag_id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS saved_views (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                query TEXT NOT NULL,
+                order_index INTEGER DEFAULT 0,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS task_activity (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                field TEXT NOT NULL,
+                old_value TEXT,
+                new_value TEXT,
+                changed_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Full-text index over project, task and todo text; trigram tokens so Japanese matches by substring
+        let search_exists = conn
+            .prepare("SELECT 1 FROM sqlite_master WHERE name = 'search_index'")?
+            .exists([])?;
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+                kind UNINDEXED,
+                ref_id UNINDEXED,
+                title,
+                body,
+                tokenize = 'trigram'
+            )",
+            [],
+        )?;
+        conn.execute_batch(SEARCH_TRIGGERS)?;
+        if !search_exists {
+            conn.execute_batch(
+                "INSERT INTO search_index (kind, ref_id, title, body)
+                    SELECT 'project', id, name, COALESCE(description, '') FROM projects;
+                 INSERT INTO search_index (kind, ref_id, title, body)
+                    SELECT 'task', id, title, COALESCE(description, '') FROM tasks;
+                 INSERT INTO search_index (kind, ref_id, title, body)
+                    SELECT 'todo', id, title, COALESCE(memo, '') FROM daily_todos;",
+            )?;
+        }
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS task_comments (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                parent_id TEXT REFERENCES task_comments(id) ON DELETE CASCADE,
+                author TEXT,
+                body TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                edited_at TEXT
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS checklist_items (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                checked INTEGER DEFAULT 0,
+                order_index INTEGER DEFAULT 0,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS attachments (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                mime TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Tombstones of deleted projects, tasks and todos for get_changes_since
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS deleted_records (
+                kind TEXT NOT NULL,
+                id TEXT NOT NULL,
+                deleted_at TEXT NOT NULL,
+                PRIMARY KEY (kind, id)
+            )",
+            [],
+        )?;
+
+        // Before the touch triggers exist, so the backfill keeps created_at as the first updated_at
+        migrate_timestamps(&conn)?;
+        for table in ["projects", "tasks", "daily_todos"] {
+            conn.execute(&format!("UPDATE {} SET updated_at = created_at WHERE updated_at IS NULL", table), [])?;
+        }
+        conn.execute_batch(VERSION_TRIGGERS)?;
+
+        // Create indexes for better performance
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks(project_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_daily_todos_date ON daily_todos(date)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_task_activity_task_id ON task_activity(task_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_task_activity_changed_at ON task_activity(changed_at)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_projects_updated_at ON projects(updated_at)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_updated_at ON tasks(updated_at)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_daily_todos_updated_at ON daily_todos(updated_at)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_deleted_records_deleted_at ON deleted_records(deleted_at)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_task_comments_task_id ON task_comments(task_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_attachments_task_id ON attachments(task_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_checklist_items_task_id ON checklist_items(task_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_time_entries_task_id ON time_entries(task_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_time_entries_todo_id ON time_entries(todo_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_task_assignees_assignee_id ON task_assignees(assignee_id)",
+            [],
+        )?;
+        // At most one running timer
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_time_entries_running ON time_entries((ended_at IS NULL)) WHERE ended_at IS NULL",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    // Project CRUD operations
+    pub fn create_project(&self, name: &str, description: Option<&str>, start_date: Option<&str>, end_date: Option<&str>) -> Result<Project> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+
+        conn.execute(
+            "INSERT INTO projects (id, name, description, start_date, end_date, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+            params![id, name, description, start_date, end_date, created_at],
+        )?;
+
+        Ok(Project {
+            id,
+            name: name.to_string(),
+            description: description.map(|s| s.to_string()),
+            start_date: start_date.map(|s| s.to_string()),
+            end_date: end_date.map(|s| s.to_string()),
+            calendar_id: None,
+            created_at: created_at.clone(),
+            version: 1,
+            updated_at: created_at,
+        })
+    }
+
+    pub fn get_all_projects(&self) -> Result<Vec<Project>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM projects ORDER BY created_at DESC", PROJECT_COLUMNS))?;
+
+        let projects = stmt.query_map([], project_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(projects)
+    }
+
+    pub fn get_project(&self, id: &str) -> Result<Option<Project>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM projects WHERE id = ?1", PROJECT_COLUMNS))?;
+
+        let mut rows = stmt.query(params![id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(project_from_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn update_project(&self, id: &str, name: &str, description: Option<&str>, start_date: Option<&str>, end_date: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE projects SET name = ?1, description = ?2, start_date = ?3, end_date = ?4 WHERE id = ?5",
+            params![name, description, start_date, end_date, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn patch_project(&self, id: &str, patch: &ProjectPatch, expected_version: i64) -> Result<PatchOutcome<Project>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let current = match tx.query_row(&format!("SELECT {} FROM projects WHERE id = ?1", PROJECT_COLUMNS), params![id], project_from_row) {
+            Ok(project) => project,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(PatchOutcome::NotFound),
+            Err(e) => return Err(e),
+        };
+        if current.version != expected_version {
+            return Ok(PatchOutcome::Stale(current));
+        }
+
+        tx.execute(
+            "UPDATE projects SET name = ?1, description = ?2, start_date = ?3, end_date = ?4 WHERE id = ?5",
+            params![
+                patch.name.as_ref().unwrap_or(&current.name),
+                patch.description.as_ref().unwrap_or(&current.description),
+                patch.start_date.as_ref().unwrap_or(&current.start_date),
+                patch.end_date.as_ref().unwrap_or(&current.end_date),
+                id
+            ],
+        )?;
+        let updated = tx.query_row(&format!("SELECT {} FROM projects WHERE id = ?1", PROJECT_COLUMNS), params![id], project_from_row)?;
+        tx.commit()?;
+        Ok(PatchOutcome::Applied(updated))
+    }
+
+    pub fn delete_project(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        Self::delete_tasks_in(&tx, PROJECT_TASKS, id)?;
+        tx.execute(
+            "DELETE FROM baseline_tasks WHERE baseline_id IN (SELECT id FROM baselines WHERE project_id = ?1)",
+            params![id],
+        )?;
+        tx.execute("DELETE FROM baselines WHERE project_id = ?1", params![id])?;
+        tx.execute(
+            "DELETE FROM task_field_values WHERE field_id IN (SELECT id FROM custom_fields WHERE project_id = ?1)",
+            params![id],
+        )?;
+        tx.execute("DELETE FROM custom_fields WHERE project_id = ?1", params![id])?;
+        tx.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
+        tx.commit()
+    }
+
+    // Task CRUD operations
+    pub fn create_task(&self, project_id: &str, parent_id: Option<&str>, title: &str, description: Option<&str>, status: TaskStatus, priority: i32, start_date: Option<&str>, end_date: Option<&str>, kind: TaskKind) -> Result<Task> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+        
+        // Get max order_index for the parent
+        let order_index: i32 = conn.query_row(
+            "SELECT COALESCE(MAX(order_index), -1) + 1 FROM tasks WHERE project_id = ?1 AND parent_id IS ?2",
+            params![project_id, parent_id],
+            |row| row.get(0),
+        )?;
+
+        conn.execute(
+            "INSERT INTO tasks (id, project_id, parent_id, title, description, status, priority, start_date, end_date, progress, order_index, kind, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0, ?10, ?11, ?12, ?12)",
+            params![id, project_id, parent_id, title, description, status, priority, start_date, end_date, order_index, kind, created_at],
+        )?;
+        Self::roll_up_summary_dates(&conn, &id)?;
+
+        Ok(Task {
+            id,
+            project_id: project_id.to_string(),
+            parent_id: parent_id.map(|s| s.to_string()),
+            title: title.to_string(),
+            description: description.map(|s| s.to_string()),
+            status,
+            priority,
+            start_date: start_date.map(|s| s.to_string()),
+            end_date: end_date.map(|s| s.to_string()),
+            progress: 0,
+            order_index,
+            estimate_hours: None,
+            kind,
+            progress_from_checklist: false,
+            created_at: created_at.clone(),
+            version: 1,
+            updated_at: created_at,
+        })
+    }
+
+    pub fn get_tasks_by_project(&self, project_id: &str) -> Result<Vec<Task>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE project_id = ?1 ORDER BY order_index",
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_map(params![project_id], task_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(tasks)
+    }
+
+    pub fn query_tasks(&self, query: &TaskQuery) -> Result<TaskPage> {
+        let (conditions, values) = task_query_conditions(query, Local::now().date_naive());
+
+        let conn = self.conn.lock().unwrap();
+        let total: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM tasks WHERE {}", conditions),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE {} ORDER BY {} LIMIT {} OFFSET {}",
+            TASK_COLUMNS,
+            conditions,
+            task_sort_clause(query.sort, query.descending),
+            query.limit.filter(|l| *l > 0).unwrap_or(-1),
+            query.offset.unwrap_or(0).max(0)
+        ))?;
+        let tasks = stmt.query_map(params_from_iter(values.iter()), task_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(TaskPage { tasks, total })
+    }
+
+    pub fn get_task(&self, id: &str) -> Result<Option<Task>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS))?;
+
+        let mut rows = stmt.query(params![id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(task_from_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn update_task(&self, id: &str, title: &str, description: Option<&str>, status: TaskStatus, priority: i32, start_date: Option<&str>, end_date: Option<&str>, progress: i32) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let current = tx.query_row(&format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS), params![id], task_from_row)?;
+        let fields = TaskPatch {
+            title: Some(title.to_string()),
+            description: Some(description.map(|s| s.to_string())),
+            status: Some(status),
+            priority: Some(priority),
+            start_date: Some(start_date.map(|s| s.to_string())),
+            end_date: Some(end_date.map(|s| s.to_string())),
+            progress: Some(progress),
+        };
+        Self::apply_task_patch(&tx, &current, &fields)?;
+        tx.commit()
+    }
+
+    pub fn patch_task(&self, id: &str, patch: &TaskPatch, expected_version: i64) -> Result<PatchOutcome<Task>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let current = match tx.query_row(&format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS), params![id], task_from_row) {
+            Ok(task) => task,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(PatchOutcome::NotFound),
+            Err(e) => return Err(e),
+        };
+        if current.version != expected_version {
+            return Ok(PatchOutcome::Stale(current));
+        }
+
+        Self::apply_task_patch(&tx, &current, patch)?;
+        let updated = tx.query_row(&format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS), params![id], task_from_row)?;
+        tx.commit()?;
+        Ok(PatchOutcome::Applied(updated))
+    }
+
+    // Writes the patched fields over `current`, logging what changed
+    fn apply_task_patch(conn: &Connection, current: &Task, patch: &TaskPatch) -> Result<()> {
+        let id = current.id.as_str();
+        let title = patch.title.as_ref().unwrap_or(&current.title);
+        let description = patch.description.as_ref().unwrap_or(&current.description);
+        let status = patch.status.unwrap_or(current.status);
+        let priority = patch.priority.unwrap_or(current.priority);
+        let start_date = patch.start_date.as_ref().unwrap_or(&current.start_date);
+        let end_date = patch.end_date.as_ref().unwrap_or(&current.end_date);
+        // Checklist-driven progress ignores the value sent by the caller
+        let progress = match Self::checklist_progress(conn, id)? {
+            Some(progress) => progress,
+            None => patch.progress.unwrap_or(current.progress),
+        };
+
+        conn.execute(
+            "UPDATE tasks SET title = ?1, description = ?2, status = ?3, priority = ?4, start_date = ?5, end_date = ?6, progress = ?7 WHERE id = ?8",
+            params![title, description, status, priority, start_date, end_date, progress, id],
+        )?;
+
+        if &current.title != title {
+            Self::record_activity(conn, id, "title", Some(&current.title), Some(title))?;
+        }
+        if current.status != status {
+            Self::record_activity(conn, id, "status", Some(current.status.as_str()), Some(status.as_str()))?;
+        }
+        if current.progress != progress {
+            Self::record_activity(conn, id, "progress", Some(&current.progress.to_string()), Some(&progress.to_string()))?;
+        }
+        // A summary's own dates are replaced by its children's span, so log what was stored
+        Self::roll_up_summary_dates(conn, id)?;
+        let (new_start, new_end) = Self::task_dates(conn, id)?;
+        Self::record_date_changes(
+            conn,
+            id,
+            (current.start_date.as_deref(), current.end_date.as_deref()),
+            (new_start.as_deref(), new_end.as_deref()),
+        )
+    }
+
+    pub fn update_task_dates(&self, id: &str, start_date: Option<&str>, end_date: Option<&str>) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let (old_start, old_end) = Self::task_dates(&tx, id)?;
+        tx.execute(
+            "UPDATE tasks SET start_date = ?1, end_date = ?2 WHERE id = ?3",
+            params![start_date, end_date, id],
+        )?;
+        Self::roll_up_summary_dates(&tx, id)?;
+        let (new_start, new_end) = Self::task_dates(&tx, id)?;
+        Self::record_date_changes(&tx, id, (old_start.as_deref(), old_end.as_deref()), (new_start.as_deref(), new_end.as_deref()))?;
+        tx.commit()
+    }
+
+    fn task_dates(conn: &Connection, id: &str) -> Result<(Option<String>, Option<String>)> {
+        conn.query_row(
+            "SELECT start_date, end_date FROM tasks WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    }
+
+    // Unfinished tasks without children, across all projects (candidates for daily todos)
+    pub fn get_open_leaf_tasks(&self) -> Result<Vec<Task>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks t
+             WHERE t.kind = 'task' AND t.status NOT IN ('completed', 'cancelled')
+               AND NOT EXISTS (SELECT 1 FROM tasks c WHERE c.parent_id = t.id)
+             ORDER BY t.project_id, t.order_index",
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_map([], task_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(tasks)
+    }
+
+    pub fn get_task_ids_with_todos_on(&self, date: &str) -> Result<HashSet<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT DISTINCT task_id FROM daily_todos WHERE date = ?1 AND task_id IS NOT NULL")?;
+        let ids = stmt.query_map(params![date], |row| row.get(0))?.collect::<Result<HashSet<String>>>()?;
+        Ok(ids)
+    }
+
+    pub fn set_task_kind(&self, id: &str, kind: TaskKind) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("UPDATE tasks SET kind = ?1 WHERE id = ?2", params![kind, id])?;
+        // Milestones collapse onto a single day
+        if kind == TaskKind::Milestone {
+            tx.execute(
+                "UPDATE tasks SET start_date = COALESCE(start_date, end_date), end_date = COALESCE(start_date, end_date) WHERE id = ?1",
+                params![id],
+            )?;
+        }
+        Self::roll_up_summary_dates(&tx, id)?;
+        tx.commit()
+    }
+
+    pub fn count_child_tasks(&self, id: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT COUNT(*) FROM tasks WHERE parent_id = ?1", params![id], |row| row.get(0))
+    }
+
+    // Summary tasks with children span them; applied from `task_id` up through its ancestors
+    fn roll_up_summary_dates(conn: &Connection, task_id: &str) -> Result<()> {
+        let mut current = Some(task_id.to_string());
+        while let Some(id) = current {
+            let mut stmt = conn.prepare("SELECT kind, parent_id FROM tasks WHERE id = ?1")?;
+            let mut rows = stmt.query(params![id])?;
+            let (kind, parent_id): (TaskKind, Option<String>) = match rows.next()? {
+                Some(row) => (row.get(0)?, row.get(1)?),
+                None => break,
+            };
+            if kind == TaskKind::Summary {
+                conn.execute(
+                    "UPDATE tasks SET
+                        start_date = (SELECT MIN(COALESCE(start_date, end_date)) FROM tasks WHERE parent_id = ?1),
+                        end_date = (SELECT MAX(COALESCE(end_date, start_date)) FROM tasks WHERE parent_id = ?1)
+                     WHERE id = ?1 AND EXISTS (SELECT 1 FROM tasks WHERE parent_id = ?1)",
+                    params![id],
+                )?;
+            }
+            current = parent_id;
+        }
+        Ok(())
+    }
+
+    // Milestones dated within the range across all projects
+    pub fn get_milestones_between(&self, start_date: &str, end_date: &str) -> Result<Vec<Milestone>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.project_id, p.name, t.title, COALESCE(t.end_date, t.start_date) as date, t.status, t.progress
+             FROM tasks t
+             JOIN projects p ON t.project_id = p.id
+             WHERE t.kind = 'milestone' AND COALESCE(t.end_date, t.start_date) BETWEEN ?1 AND ?2
+             ORDER BY date, p.name",
+        )?;
+
+        let milestones = stmt.query_map(params![start_date, end_date], |row| {
+            Ok(Milestone {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                project_name: row.get(2)?,
+                title: row.get(3)?,
+                date: row.get(4)?,
+                status: row.get(5)?,
+                progress: row.get(6)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(milestones)
+    }
+
+    pub fn update_task_estimate(&self, id: &str, estimate_hours: Option<f64>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE tasks SET estimate_hours = ?1 WHERE id = ?2",
+            params![estimate_hours, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_task(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        Self::delete_task_row(&tx, id)?;
+        tx.commit()
+    }
+
+    // Deletes the task together with its subtree
+    fn delete_task_row(conn: &Connection, id: &str) -> Result<()> {
+        let parent_id: Option<String> = conn
+            .query_row("SELECT parent_id FROM tasks WHERE id = ?1", params![id], |row| row.get(0))
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                e => Err(e),
+            })?;
+        Self::delete_tasks_in(conn, TASK_SUBTREE, id)?;
+        if let Some(parent_id) = parent_id {
+            Self::roll_up_summary_dates(conn, &parent_id)?;
+        }
+        Ok(())
+    }
+
+    // Foreign keys are off, so the schema's ON DELETE actions never run; this applies them by
+    // hand for the tasks selected by `target` (a CTE binding `key` as ?1)
+    fn delete_tasks_in(conn: &Connection, target: &str, key: &str) -> Result<()> {
+        for table in TASK_OWNED_TABLES {
+            conn.execute(&format!("{} DELETE FROM {} WHERE task_id IN target", target, table), params![key])?;
+        }
+        for table in TASK_LINKED_TABLES {
+            conn.execute(&format!("{} UPDATE {} SET task_id = NULL WHERE task_id IN target", target, table), params![key])?;
+        }
+        conn.execute(&format!("{} DELETE FROM tasks WHERE id IN target", target), params![key])?;
+        Ok(())
+    }
+
+    // Task activity log
+    fn record_activity(conn: &Connection, task_id: &str, field: &str, old_value: Option<&str>, new_value: Option<&str>) -> Result<()> {
+        let id = Uuid::new_v4().to_string();
+        let changed_at = now_timestamp();
+
+        conn.execute(
+            "INSERT INTO task_activity (id, task_id, field, old_value, new_value, changed_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, task_id, field, old_value, new_value, changed_at],
+        )?;
+        Ok(())
+    }
+
+    fn record_date_changes(conn: &Connection, task_id: &str, old: (Option<&str>, Option<&str>), new: (Option<&str>, Option<&str>)) -> Result<()> {
+        if old.0 != new.0 {
+            Self::record_activity(conn, task_id, "start_date", old.0, new.0)?;
+        }
+        if old.1 != new.1 {
+            Self::record_activity(conn, task_id, "end_date", old.1, new.1)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_task_activity(&self, task_id: &str) -> Result<Vec<TaskActivity>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT a.id, a.task_id, a.field, a.old_value, a.new_value, a.changed_at, t.title, p.name
+             FROM task_activity a
+             JOIN tasks t ON a.task_id = t.id
+             JOIN projects p ON t.project_id = p.id
+             WHERE a.task_id = ?1
+             ORDER BY a.changed_at"
+        )?;
+
+        let activity = stmt.query_map(params![task_id], |row| {
+            Ok(TaskActivity {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                field: row.get(2)?,
+                old_value: row.get(3)?,
+                new_value: row.get(4)?,
+                changed_at: row.get(5)?,
+                task_title: row.get(6)?,
+                project_name: row.get(7)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(activity)
+    }
+
+    // Checklists
+    pub fn add_checklist_item(&self, task_id: &str, title: &str) -> Result<ChecklistItem> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+        let order_index: i32 = tx.query_row(
+            "SELECT COALESCE(MAX(order_index), -1) + 1 FROM checklist_items WHERE task_id = ?1",
+            params![task_id],
+            |row| row.get(0),
+        )?;
+
+        tx.execute(
+            "INSERT INTO checklist_items (id, task_id, title, checked, order_index, created_at) VALUES (?1, ?2, ?3, 0, ?4, ?5)",
+            params![id, task_id, title, order_index, created_at],
+        )?;
+        Self::sync_checklist_progress(&tx, task_id)?;
+        tx.commit()?;
+
+        Ok(ChecklistItem {
+            id,
+            task_id: task_id.to_string(),
+            title: title.to_string(),
+            checked: false,
+            order_index,
+            created_at,
+        })
+    }
+
+    pub fn get_checklist_items(&self, task_id: &str) -> Result<Vec<ChecklistItem>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, task_id, title, checked, order_index, created_at FROM checklist_items WHERE task_id = ?1 ORDER BY order_index",
+        )?;
+
+        let items = stmt.query_map(params![task_id], |row| {
+            Ok(ChecklistItem {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                title: row.get(2)?,
+                checked: row.get::<_, i32>(3)? == 1,
+                order_index: row.get(4)?,
                 created_at: row.get(5)?,
-            }))
-        } else {
-            Ok(None)
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(items)
+    }
+
+    pub fn update_checklist_item(&self, id: &str, title: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE checklist_items SET title = ?1 WHERE id = ?2", params![title, id])?;
+        Ok(())
+    }
+
+    pub fn toggle_checklist_item(&self, id: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let (task_id, current): (String, i32) = tx.query_row(
+            "SELECT task_id, checked FROM checklist_items WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let new_value = if current == 1 { 0 } else { 1 };
+        tx.execute("UPDATE checklist_items SET checked = ?1 WHERE id = ?2", params![new_value, id])?;
+        Self::sync_checklist_progress(&tx, &task_id)?;
+        tx.commit()?;
+        Ok(new_value == 1)
+    }
+
+    pub fn reorder_checklist_items(&self, task_id: &str, ids: &[String]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for (index, id) in ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE checklist_items SET order_index = ?1 WHERE id = ?2 AND task_id = ?3",
+                params![index as i32, id, task_id],
+            )?;
+        }
+        tx.commit()
+    }
+
+    pub fn delete_checklist_item(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let task_id: Option<String> = tx
+            .query_row("SELECT task_id FROM checklist_items WHERE id = ?1", params![id], |row| row.get(0))
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                e => Err(e),
+            })?;
+        tx.execute("DELETE FROM checklist_items WHERE id = ?1", params![id])?;
+        if let Some(task_id) = task_id {
+            Self::sync_checklist_progress(&tx, &task_id)?;
+        }
+        tx.commit()
+    }
+
+    pub fn set_progress_from_checklist(&self, task_id: &str, enabled: bool) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE tasks SET progress_from_checklist = ?1 WHERE id = ?2",
+            params![enabled, task_id],
+        )?;
+        Self::sync_checklist_progress(&tx, task_id)?;
+        tx.commit()
+    }
+
+    // Recomputes progress from the checked ratio when the task opted in and has items
+    fn sync_checklist_progress(conn: &Connection, task_id: &str) -> Result<()> {
+        let progress = match Self::checklist_progress(conn, task_id)? {
+            Some(progress) => progress,
+            None => return Ok(()),
+        };
+        let old_progress: i32 = conn.query_row("SELECT progress FROM tasks WHERE id = ?1", params![task_id], |row| row.get(0))?;
+        if progress != old_progress {
+            conn.execute("UPDATE tasks SET progress = ?1 WHERE id = ?2", params![progress, task_id])?;
+            Self::record_activity(conn, task_id, "progress", Some(&old_progress.to_string()), Some(&progress.to_string()))?;
+        }
+        Ok(())
+    }
+
+    // Checklist completion ratio when it drives the task's progress; None when the flag is off
+    // or the checklist is empty
+    fn checklist_progress(conn: &Connection, task_id: &str) -> Result<Option<i32>> {
+        let (enabled, total, checked): (bool, i64, i64) = conn.query_row(
+            "SELECT t.progress_from_checklist,
+                    (SELECT COUNT(*) FROM checklist_items WHERE task_id = t.id),
+                    (SELECT COUNT(*) FROM checklist_items WHERE task_id = t.id AND checked = 1)
+             FROM tasks t WHERE t.id = ?1",
+            params![task_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        Ok((enabled && total > 0).then(|| (checked * 100 / total) as i32))
+    }
+
+    // Attachments
+    pub fn add_attachment(&self, task_id: &str, name: &str, size: i64, mime: &str, hash: &str) -> Result<Attachment> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+
+        conn.execute(
+            "INSERT INTO attachments (id, task_id, name, size, mime, hash, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![id, task_id, name, size, mime, hash, created_at],
+        )?;
+
+        Ok(Attachment {
+            id,
+            task_id: task_id.to_string(),
+            name: name.to_string(),
+            size,
+            mime: mime.to_string(),
+            hash: hash.to_string(),
+            created_at,
+        })
+    }
+
+    pub fn get_attachments(&self, task_id: &str) -> Result<Vec<Attachment>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, task_id, name, size, mime, hash, created_at FROM attachments WHERE task_id = ?1 ORDER BY created_at",
+        )?;
+
+        let attachments = stmt.query_map(params![task_id], attachment_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(attachments)
+    }
+
+    pub fn get_attachment(&self, id: &str) -> Result<Option<Attachment>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, task_id, name, size, mime, hash, created_at FROM attachments WHERE id = ?1")?;
+
+        let mut rows = stmt.query(params![id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(attachment_from_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn delete_attachment(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM attachments WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // Drops rows whose task is gone and returns the hashes still in use
+    pub fn prune_attachments(&self) -> Result<HashSet<String>> {
+        let conn = self.conn.lock().unwrap();
+        // Also catches tasks left behind by projects deleted before deletes removed their tasks
+        conn.execute(
+            "DELETE FROM attachments WHERE task_id NOT IN (SELECT t.id FROM tasks t JOIN projects p ON p.id = t.project_id)",
+            [],
+        )?;
+        let mut stmt = conn.prepare("SELECT DISTINCT hash FROM attachments")?;
+        let hashes = stmt.query_map([], |row| row.get(0))?.collect::<Result<HashSet<String>>>()?;
+        Ok(hashes)
+    }
+
+    // Consistent copy of the database file, safe to take while the app is running
+    pub fn backup_to(&self, path: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("VACUUM INTO ?1", params![path])?;
+        Ok(())
+    }
+
+    // Comments
+    pub fn add_task_comment(&self, task_id: &str, parent_id: Option<&str>, author: Option<&str>, body: &str) -> Result<TaskComment> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+
+        conn.execute(
+            "INSERT INTO task_comments (id, task_id, parent_id, author, body, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, task_id, parent_id, author, body, created_at],
+        )?;
+
+        Ok(TaskComment {
+            id,
+            task_id: task_id.to_string(),
+            parent_id: parent_id.map(|s| s.to_string()),
+            author: author.map(|s| s.to_string()),
+            body: body.to_string(),
+            created_at,
+            edited_at: None,
+        })
+    }
+
+    pub fn get_task_comment(&self, id: &str) -> Result<Option<TaskComment>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, task_id, parent_id, author, body, created_at, edited_at FROM task_comments WHERE id = ?1",
+        )?;
+
+        let mut rows = stmt.query(params![id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(task_comment_from_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_task_comments(&self, task_id: &str) -> Result<Vec<TaskComment>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, task_id, parent_id, author, body, created_at, edited_at FROM task_comments WHERE task_id = ?1 ORDER BY created_at",
+        )?;
+
+        let comments = stmt.query_map(params![task_id], task_comment_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(comments)
+    }
+
+    pub fn update_task_comment(&self, id: &str, body: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let edited_at = now_timestamp();
+        conn.execute(
+            "UPDATE task_comments SET body = ?1, edited_at = ?2 WHERE id = ?3",
+            params![body, edited_at, id],
+        )?;
+        Ok(())
+    }
+
+    // Removes the comment together with its replies
+    pub fn delete_task_comment(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "WITH RECURSIVE thread(id) AS (
+                SELECT ?1
+                UNION ALL
+                SELECT c.id FROM task_comments c JOIN thread ON c.parent_id = thread.id
+             )
+             DELETE FROM task_comments WHERE id IN (SELECT id FROM thread)",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_task_activity_between(&self, start_date: &str, end_date: &str) -> Result<Vec<TaskActivity>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT a.id, a.task_id, a.field, a.old_value, a.new_value, a.changed_at, t.title, p.name
+             FROM task_activity a
+             JOIN tasks t ON a.task_id = t.id
+             JOIN projects p ON t.project_id = p.id
+             WHERE date(a.changed_at, 'localtime') BETWEEN ?1 AND ?2
+             ORDER BY a.changed_at"
+        )?;
+
+        let activity = stmt.query_map(params![start_date, end_date], |row| {
+            Ok(TaskActivity {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                field: row.get(2)?,
+                old_value: row.get(3)?,
+                new_value: row.get(4)?,
+                changed_at: row.get(5)?,
+                task_title: row.get(6)?,
+                project_name: row.get(7)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(activity)
+    }
+
+    // `since` is a UTC timestamp as produced by now_timestamp. The bound is inclusive so a write in
+    // the same millisecond as the previous `until` is not lost; such rows may be returned twice
+    pub fn get_changes_since(&self, since: &str) -> Result<ChangeSet> {
+        let conn = self.conn.lock().unwrap();
+        // Writes are serialized on this lock, so nothing can land between the snapshot and the queries
+        let until = now_timestamp();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM projects WHERE updated_at >= ?1 ORDER BY updated_at",
+            PROJECT_COLUMNS
+        ))?;
+        let projects = stmt.query_map(params![since], project_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE updated_at >= ?1 ORDER BY updated_at",
+            TASK_COLUMNS
+        ))?;
+        let tasks = stmt.query_map(params![since], task_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE dt.updated_at >= ?1 ORDER BY dt.updated_at",
+            TODO_WITH_TASK_SELECT
+        ))?;
+        let todos = stmt.query_map(params![since], todo_with_task_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT kind, id, deleted_at FROM deleted_records WHERE deleted_at >= ?1 ORDER BY deleted_at",
+        )?;
+        let deleted = stmt.query_map(params![since], |row| {
+            Ok(DeletedRecord {
+                kind: row.get(0)?,
+                id: row.get(1)?,
+                deleted_at: row.get(2)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(ChangeSet {
+            since: since.to_string(),
+            until,
+            projects,
+            tasks,
+            todos,
+            deleted,
+        })
+    }
+
+    // Daily TODO CRUD operations
+    pub fn create_daily_todo(&self, task_id: Option<&str>, title: &str, date: &str, memo: Option<&str>) -> Result<DailyTodo> {
+        let conn = self.conn.lock().unwrap();
+        Self::insert_daily_todo(&conn, task_id, title, date, memo)
+    }
+
+    fn insert_daily_todo(conn: &Connection, task_id: Option<&str>, title: &str, date: &str, memo: Option<&str>) -> Result<DailyTodo> {
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+        let order_index = next_todo_order(conn, date)?;
+
+        conn.execute(
+            "INSERT INTO daily_todos (id, task_id, title, date, completed, memo, order_index, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6, ?7, ?7)",
+            params![id, task_id, title, date, memo, order_index, created_at],
+        )?;
+
+        Ok(DailyTodo {
+            id,
+            task_id: task_id.map(|s| s.to_string()),
+            title: title.to_string(),
+            date: date.to_string(),
+            completed: false,
+            memo: memo.map(|s| s.to_string()),
+            carry_count: 0,
+            carried_from: None,
+            order_index,
+            priority: 0,
+            scheduled_time: None,
+            duration_minutes: None,
+            is_final: false,
+            created_at: created_at.clone(),
+            updated_at: created_at,
+        })
+    }
+
+    pub fn get_todos_by_date(&self, date: &str) -> Result<Vec<DailyTodoWithTask>> {
+        self.materialize_recurring_todos(date)?;
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE dt.date = ?1 ORDER BY dt.completed, dt.order_index, dt.created_at",
+            TODO_WITH_TASK_SELECT
+        ))?;
+
+        let todos = stmt.query_map(params![date], todo_with_task_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(todos)
+    }
+
+    pub fn get_todos_between(&self, start_date: &str, end_date: &str) -> Result<Vec<DailyTodoWithTask>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE dt.date BETWEEN ?1 AND ?2 ORDER BY dt.date, dt.completed, dt.order_index, dt.created_at",
+            TODO_WITH_TASK_SELECT
+        ))?;
+
+        let todos = stmt.query_map(params![start_date, end_date], todo_with_task_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(todos)
+    }
+
+    pub fn toggle_todo(&self, id: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let (current, task_id, is_final): (i32, Option<String>, i32) = tx.query_row(
+            "SELECT completed, task_id, is_final FROM daily_todos WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        let new_value = if current == 1 { 0 } else { 1 };
+        tx.execute(
+            "UPDATE daily_todos SET completed = ?1 WHERE id = ?2",
+            params![new_value, id],
+        )?;
+        if let Some(task_id) = task_id {
+            Self::sync_task_from_todo(&tx, &task_id, new_value == 1, is_final == 1)?;
+        }
+        tx.commit()?;
+        Ok(new_value == 1)
+    }
+
+    // Applies the todo sync rules to the linked task; the task may have been deleted
+    fn sync_task_from_todo(conn: &Connection, task_id: &str, completed: bool, is_final: bool) -> Result<()> {
+        Self::sync_task_status(conn, task_id, |rules, status| rules.task_status_after_toggle(status, completed, is_final))
+    }
+
+    fn sync_task_status(
+        conn: &Connection,
+        task_id: &str,
+        next_status: impl FnOnce(&TodoSyncRules, TaskStatus) -> Option<TaskStatus>,
+    ) -> Result<()> {
+        let rules = Self::load_sync_rules(conn)?;
+        let mut stmt = conn.prepare("SELECT status, progress FROM tasks WHERE id = ?1")?;
+        let mut rows = stmt.query(params![task_id])?;
+        let (old_status, old_progress): (TaskStatus, i32) = match rows.next()? {
+            Some(row) => (row.get(0)?, row.get(1)?),
+            None => return Ok(()),
+        };
+
+        let status = match next_status(&rules, old_status) {
+            Some(status) => status,
+            None => return Ok(()),
+        };
+        let progress = Self::progress_after_status(conn, task_id, status, old_progress)?;
+        conn.execute(
+            "UPDATE tasks SET status = ?1, progress = ?2 WHERE id = ?3",
+            params![status, progress, task_id],
+        )?;
+        Self::record_activity(conn, task_id, "status", Some(old_status.as_str()), Some(status.as_str()))?;
+        if progress != old_progress {
+            Self::record_activity(conn, task_id, "progress", Some(&old_progress.to_string()), Some(&progress.to_string()))?;
+        }
+        Ok(())
+    }
+
+    // Checklist-driven progress wins over the status; otherwise completing a task means 100%
+    fn progress_after_status(conn: &Connection, task_id: &str, status: TaskStatus, old_progress: i32) -> Result<i32> {
+        if let Some(progress) = Self::checklist_progress(conn, task_id)? {
+            return Ok(progress);
+        }
+        Ok(if status == TaskStatus::Completed { 100 } else { old_progress })
+    }
+
+    fn load_sync_rules(conn: &Connection) -> Result<TodoSyncRules> {
+        let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
+        let mut rows = stmt.query(params![SYNC_RULES_SETTING])?;
+        match rows.next()? {
+            Some(row) => {
+                let value: String = row.get(0)?;
+                serde_json::from_str(&value).map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e)))
+            }
+            None => Ok(TodoSyncRules::default()),
+        }
+    }
+
+    pub fn get_sync_rules(&self) -> Result<TodoSyncRules> {
+        let conn = self.conn.lock().unwrap();
+        Self::load_sync_rules(&conn)
+    }
+
+    pub fn set_sync_rules(&self, rules: &TodoSyncRules) -> Result<()> {
+        let value = serde_json::to_string(rules).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.set_setting(SYNC_RULES_SETTING, &value)
+    }
+
+    pub fn set_todo_final(&self, id: &str, is_final: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE daily_todos SET is_final = ?1 WHERE id = ?2",
+            params![is_final, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_todo_memo(&self, id: &str, memo: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE daily_todos SET memo = ?1 WHERE id = ?2",
+            params![memo, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_todo_schedule(&self, id: &str, priority: i32, scheduled_time: Option<&str>, duration_minutes: Option<i32>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE daily_todos SET priority = ?1, scheduled_time = ?2, duration_minutes = ?3 WHERE id = ?4",
+            params![priority, scheduled_time, duration_minutes, id],
+        )?;
+        Ok(())
+    }
+
+    // Todos of `date` missing from `ids` keep their relative order after the listed ones
+    pub fn reorder_todos(&self, date: &str, ids: &[String]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let rest = {
+            let mut stmt = tx.prepare("SELECT id FROM daily_todos WHERE date = ?1 ORDER BY order_index, created_at")?;
+            let rows = stmt
+                .query_map(params![date], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>>>()?;
+            rows.into_iter().filter(|id| !ids.contains(id)).collect::<Vec<_>>()
+        };
+        for (index, id) in ids.iter().chain(rest.iter()).enumerate() {
+            tx.execute(
+                "UPDATE daily_todos SET order_index = ?1 WHERE id = ?2 AND date = ?3",
+                params![index as i32, id, date],
+            )?;
+        }
+        tx.commit()
+    }
+
+    // Foreign keys are off, so the todo's ON DELETE actions are applied here
+    pub fn delete_todo(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM todo_tags WHERE todo_id = ?1", params![id])?;
+        tx.execute("UPDATE time_entries SET todo_id = NULL WHERE todo_id = ?1", params![id])?;
+        tx.execute("UPDATE daily_todos SET carried_from = NULL WHERE carried_from = ?1", params![id])?;
+        // The instance row stays so the recurring todo is not materialized again for that date
+        tx.execute("UPDATE recurring_todo_instances SET todo_id = NULL WHERE todo_id = ?1", params![id])?;
+        tx.execute("DELETE FROM daily_todos WHERE id = ?1", params![id])?;
+        tx.commit()
+    }
+
+    // Carry over incomplete todos from a single date
+    pub fn carry_over_todos(&self, from_date: &str, to_date: &str, mode: CarryOverMode) -> Result<Vec<DailyTodo>> {
+        self.carry_over("dt.date = ?1", from_date, to_date, mode)
+    }
+
+    // Carry over every incomplete todo dated before `to_date` (used on app start)
+    pub fn carry_over_past_todos(&self, to_date: &str, mode: CarryOverMode) -> Result<Vec<DailyTodo>> {
+        self.carry_over("dt.date < ?1", to_date, to_date, mode)
+    }
+
+    fn carry_over(&self, date_filter: &str, date_param: &str, to_date: &str, mode: CarryOverMode) -> Result<Vec<DailyTodo>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        // Todos that were already copied forward are skipped so repeated runs don't duplicate them
+        let sources = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT dt.id, dt.task_id, dt.title, dt.memo, dt.carry_count, dt.priority, dt.scheduled_time, dt.duration_minutes, dt.is_final
+                 FROM daily_todos dt
+                 WHERE {} AND dt.date != ?2 AND dt.completed = 0
+                   AND NOT EXISTS (SELECT 1 FROM daily_todos c WHERE c.carried_from = dt.id)
+                 ORDER BY dt.date, dt.order_index, dt.created_at",
+                date_filter
+            ))?;
+            let rows = stmt.query_map(params![date_param, to_date], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, i32>(4)?,
+                    row.get::<_, i32>(5)?,
+                    row.get::<_, Option<String>>(6)?,
+                    row.get::<_, Option<i32>>(7)?,
+                    row.get::<_, i32>(8)? == 1,
+                ))
+            })?.collect::<Result<Vec<_>>>()?;
+            rows
+        };
+
+        let mut carried = Vec::new();
+        for (source_id, task_id, title, memo, carry_count, priority, scheduled_time, duration_minutes, is_final) in sources {
+            let carry_count = carry_count + 1;
+            let order_index = next_todo_order(&tx, to_date)?;
+            match mode {
+                CarryOverMode::Move => {
+                    tx.execute(
+                        "UPDATE daily_todos SET date = ?1, carry_count = ?2, order_index = ?3 WHERE id = ?4",
+                        params![to_date, carry_count, order_index, source_id],
+                    )?;
+                    let (created_at, updated_at): (String, String) = tx.query_row(
+                        "SELECT created_at, updated_at FROM daily_todos WHERE id = ?1",
+                        params![source_id],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )?;
+                    carried.push(DailyTodo {
+                        id: source_id,
+                        task_id,
+                        title,
+                        date: to_date.to_string(),
+                        completed: false,
+                        memo,
+                        carry_count,
+                        carried_from: None,
+                        order_index,
+                        priority,
+                        scheduled_time,
+                        duration_minutes,
+                        is_final,
+                        created_at,
+                        updated_at,
+                    });
+                }
+                CarryOverMode::Copy => {
+                    let id = Uuid::new_v4().to_string();
+                    let created_at = now_timestamp();
+                    tx.execute(
+                        "INSERT INTO daily_todos (id, task_id, title, date, completed, memo, carry_count, carried_from, order_index, priority, scheduled_time, duration_minutes, is_final, created_at, updated_at)
+                         VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?13)",
+                        params![id, task_id, title, to_date, memo, carry_count, source_id, order_index, priority, scheduled_time, duration_minutes, is_final, created_at],
+                    )?;
+                    carried.push(DailyTodo {
+                        id,
+                        task_id,
+                        title,
+                        date: to_date.to_string(),
+                        completed: false,
+                        memo,
+                        carry_count,
+                        carried_from: Some(source_id),
+                        order_index,
+                        priority,
+                        scheduled_time,
+                        duration_minutes,
+                        is_final,
+                        created_at: created_at.clone(),
+                        updated_at: created_at,
+                    });
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(carried)
+    }
+
+    pub fn add_task_to_todo(&self, task_id: &str, date: &str) -> Result<DailyTodo> {
+        let conn = self.conn.lock().unwrap();
+        
+        // Get task title
+        let title: String = conn.query_row(
+            "SELECT title FROM tasks WHERE id = ?1",
+            params![task_id],
+            |row| row.get(0),
+        )?;
+
+        drop(conn);
+        
+        self.create_daily_todo(Some(task_id), &title, date, None)
+    }
+
+    // Recurring TODO operations
+    pub fn create_recurring_todo(&self, task_id: Option<&str>, title: &str, memo: Option<&str>, rule: &RecurrenceRule, start_date: &str, end_date: Option<&str>) -> Result<RecurringTodo> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+
+        conn.execute(
+            "INSERT INTO recurring_todos (id, task_id, title, memo, rule, start_date, end_date, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![id, task_id, title, memo, rule_to_json(rule)?, start_date, end_date, created_at],
+        )?;
+
+        Ok(RecurringTodo {
+            id,
+            task_id: task_id.map(|s| s.to_string()),
+            title: title.to_string(),
+            memo: memo.map(|s| s.to_string()),
+            rule: rule.clone(),
+            start_date: start_date.to_string(),
+            end_date: end_date.map(|s| s.to_string()),
+            created_at,
+        })
+    }
+
+    pub fn get_recurring_todos(&self) -> Result<Vec<RecurringTodo>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, task_id, title, memo, rule, start_date, end_date, created_at FROM recurring_todos ORDER BY created_at"
+        )?;
+
+        let rules = stmt.query_map([], recurring_todo_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(rules)
+    }
+
+    pub fn update_recurring_todo(&self, id: &str, task_id: Option<&str>, title: &str, memo: Option<&str>, rule: &RecurrenceRule, start_date: &str, end_date: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE recurring_todos SET task_id = ?1, title = ?2, memo = ?3, rule = ?4, start_date = ?5, end_date = ?6 WHERE id = ?7",
+            params![task_id, title, memo, rule_to_json(rule)?, start_date, end_date, id],
+        )?;
+        Ok(())
+    }
+
+    // Already materialized todos are kept; only future dates stop being generated
+    pub fn delete_recurring_todo(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM recurring_todo_instances WHERE recurring_id = ?1", params![id])?;
+        conn.execute("DELETE FROM recurring_todos WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // Create the daily todos for every recurring rule that falls on `date` and hasn't been materialized yet
+    fn materialize_recurring_todos(&self, date: &str) -> Result<()> {
+        let target = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(target) => target,
+            Err(_) => return Ok(()),
+        };
+
+        let (calendar, holidays) = self.load_calendar(None)?;
+        let work_calendar = WorkCalendar::new(&calendar, &holidays);
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let rules = {
+            let mut stmt = tx.prepare(
+                "SELECT r.id, r.task_id, r.title, r.memo, r.rule, r.start_date, r.end_date, r.created_at
+                 FROM recurring_todos r
+                 WHERE r.start_date <= ?1 AND (r.end_date IS NULL OR r.end_date >= ?1)
+                   AND NOT EXISTS (SELECT 1 FROM recurring_todo_instances i WHERE i.recurring_id = r.id AND i.date = ?1)"
+            )?;
+            let rows = stmt.query_map(params![date], recurring_todo_from_row)?.collect::<Result<Vec<_>>>()?;
+            rows
+        };
+
+        for rule in rules {
+            let start = match NaiveDate::parse_from_str(&rule.start_date, "%Y-%m-%d") {
+                Ok(start) => start,
+                Err(_) => continue,
+            };
+            if !rule.rule.occurs_on(start, target, &work_calendar) {
+                continue;
+            }
+
+            let id = Uuid::new_v4().to_string();
+            let created_at = now_timestamp();
+            let order_index = next_todo_order(&tx, date)?;
+            tx.execute(
+                "INSERT INTO daily_todos (id, task_id, title, date, completed, memo, order_index, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6, ?7, ?7)",
+                params![id, rule.task_id, rule.title, date, rule.memo, order_index, created_at],
+            )?;
+            tx.execute(
+                "INSERT INTO recurring_todo_instances (recurring_id, date, todo_id) VALUES (?1, ?2, ?3)",
+                params![rule.id, date, id],
+            )?;
+        }
+
+        tx.commit()
+    }
+
+    // Time tracking
+    fn resolve_time_entry_task(conn: &Connection, todo_id: Option<&str>, task_id: Option<&str>) -> Result<Option<String>> {
+        // Entries logged against a linked todo also count towards its task
+        match (task_id, todo_id) {
+            (Some(task_id), _) => Ok(Some(task_id.to_string())),
+            (None, Some(todo_id)) => conn.query_row(
+                "SELECT task_id FROM daily_todos WHERE id = ?1",
+                params![todo_id],
+                |row| row.get(0),
+            ),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn stop_running_timer(conn: &Connection, ended_at: &str) -> Result<Option<TimeEntry>> {
+        let running = conn.query_row(
+            &format!("SELECT {} FROM time_entries WHERE ended_at IS NULL", TIME_ENTRY_COLUMNS),
+            [],
+            time_entry_from_row,
+        );
+        let mut entry = match running {
+            Ok(entry) => entry,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let duration = seconds_between(&entry.started_at, ended_at);
+        conn.execute(
+            "UPDATE time_entries SET ended_at = ?1, duration_seconds = ?2 WHERE id = ?3",
+            params![ended_at, duration, entry.id],
+        )?;
+        entry.ended_at = Some(ended_at.to_string());
+        entry.duration_seconds = Some(duration);
+        Ok(Some(entry))
+    }
+
+    // Starting a timer stops the one that is currently running
+    pub fn start_timer(&self, todo_id: Option<&str>, task_id: Option<&str>, note: Option<&str>) -> Result<TimeEntry> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let now = now_timestamp();
+
+        Self::stop_running_timer(&tx, &now)?;
+        let task_id = Self::resolve_time_entry_task(&tx, todo_id, task_id)?;
+        if let Some(ref task_id) = task_id {
+            Self::sync_task_status(&tx, task_id, |rules, status| rules.task_status_on_start(status))?;
+        }
+
+        let id = Uuid::new_v4().to_string();
+        tx.execute(
+            "INSERT INTO time_entries (id, todo_id, task_id, started_at, ended_at, duration_seconds, note, created_at) VALUES (?1, ?2, ?3, ?4, NULL, NULL, ?5, ?4)",
+            params![id, todo_id, task_id, now, note],
+        )?;
+        tx.commit()?;
+
+        Ok(TimeEntry {
+            id,
+            todo_id: todo_id.map(|s| s.to_string()),
+            task_id,
+            started_at: now.clone(),
+            ended_at: None,
+            duration_seconds: None,
+            note: note.map(|s| s.to_string()),
+            created_at: now,
+        })
+    }
+
+    pub fn stop_timer(&self) -> Result<Option<TimeEntry>> {
+        let conn = self.conn.lock().unwrap();
+        Self::stop_running_timer(&conn, &now_timestamp())
+    }
+
+    pub fn get_running_timer(&self) -> Result<Option<TimeEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM time_entries WHERE ended_at IS NULL", TIME_ENTRY_COLUMNS))?;
+        let mut rows = stmt.query([])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(time_entry_from_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn add_time_entry(&self, todo_id: Option<&str>, task_id: Option<&str>, started_at: &str, ended_at: &str, note: Option<&str>) -> Result<TimeEntry> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+        let task_id = Self::resolve_time_entry_task(&conn, todo_id, task_id)?;
+        let duration = seconds_between(started_at, ended_at);
+
+        conn.execute(
+            "INSERT INTO time_entries (id, todo_id, task_id, started_at, ended_at, duration_seconds, note, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![id, todo_id, task_id, started_at, ended_at, duration, note, created_at],
+        )?;
+
+        Ok(TimeEntry {
+            id,
+            todo_id: todo_id.map(|s| s.to_string()),
+            task_id,
+            started_at: started_at.to_string(),
+            ended_at: Some(ended_at.to_string()),
+            duration_seconds: Some(duration),
+            note: note.map(|s| s.to_string()),
+            created_at,
+        })
+    }
+
+    pub fn get_time_entries(&self, todo_id: Option<&str>, task_id: Option<&str>) -> Result<Vec<TimeEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM time_entries WHERE (?1 IS NULL OR todo_id = ?1) AND (?2 IS NULL OR task_id = ?2) ORDER BY started_at",
+            TIME_ENTRY_COLUMNS
+        ))?;
+
+        let entries = stmt.query_map(params![todo_id, task_id], time_entry_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(entries)
+    }
+
+    pub fn delete_time_entry(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM time_entries WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    fn time_totals(&self, key_sql: &str, label_sql: &str, start_date: &str, end_date: &str) -> Result<Vec<TimeTotal>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {key} as key, {label} as label, SUM(te.duration_seconds) as seconds
+             FROM time_entries te
+             LEFT JOIN tasks t ON te.task_id = t.id
+             LEFT JOIN projects p ON t.project_id = p.id
+             WHERE te.ended_at IS NOT NULL AND date(te.started_at, 'localtime') BETWEEN ?1 AND ?2
+             GROUP BY {key}
+             ORDER BY {key}",
+            key = key_sql,
+            label = label_sql
+        ))?;
+
+        let totals = stmt.query_map(params![start_date, end_date], |row| {
+            Ok(TimeTotal {
+                key: row.get(0)?,
+                label: row.get(1)?,
+                seconds: row.get(2)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(totals)
+    }
+
+    // Tracked seconds per task of a project, up to and including `as_of`
+    pub fn get_actual_seconds_by_task(&self, project_id: &str, as_of: &str) -> Result<Vec<(String, i64)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT te.task_id, SUM(te.duration_seconds)
+             FROM time_entries te
+             JOIN tasks t ON te.task_id = t.id
+             WHERE t.project_id = ?1 AND te.ended_at IS NOT NULL AND date(te.started_at, 'localtime') <= ?2
+             GROUP BY te.task_id"
+        )?;
+
+        let actuals = stmt.query_map(params![project_id, as_of], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(actuals)
+    }
+
+    pub fn get_time_by_task(&self, start_date: &str, end_date: &str) -> Result<Vec<TimeTotal>> {
+        self.time_totals("te.task_id", "MAX(t.title)", start_date, end_date)
+    }
+
+    pub fn get_time_by_project(&self, start_date: &str, end_date: &str) -> Result<Vec<TimeTotal>> {
+        self.time_totals("t.project_id", "MAX(p.name)", start_date, end_date)
+    }
+
+    pub fn get_time_by_day(&self, start_date: &str, end_date: &str) -> Result<Vec<TimeTotal>> {
+        self.time_totals("date(te.started_at, 'localtime')", "date(te.started_at, 'localtime')", start_date, end_date)
+    }
+
+    // Assignee CRUD operations
+    pub fn create_assignee(&self, name: &str, email: Option<&str>) -> Result<Assignee> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+
+        conn.execute(
+            "INSERT INTO assignees (id, name, email, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![id, name, email, created_at],
+        )?;
+
+        Ok(Assignee {
+            id,
+            name: name.to_string(),
+            email: email.map(|s| s.to_string()),
+            created_at,
+        })
+    }
+
+    pub fn get_all_assignees(&self) -> Result<Vec<Assignee>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name, email, created_at FROM assignees ORDER BY name")?;
+
+        let assignees = stmt.query_map([], |row| {
+            Ok(Assignee {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                email: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(assignees)
+    }
+
+    pub fn update_assignee(&self, id: &str, name: &str, email: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE assignees SET name = ?1, email = ?2 WHERE id = ?3",
+            params![name, email, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_assignee(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM task_assignees WHERE assignee_id = ?1", params![id])?;
+        conn.execute("DELETE FROM assignees WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // Assigning the same person twice updates the allocation
+    pub fn assign_task(&self, task_id: &str, assignee_id: &str, allocation: i32) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO task_assignees (task_id, assignee_id, allocation) VALUES (?1, ?2, ?3)
+             ON CONFLICT(task_id, assignee_id) DO UPDATE SET allocation = excluded.allocation",
+            params![task_id, assignee_id, allocation],
+        )?;
+        Ok(())
+    }
+
+    pub fn unassign_task(&self, task_id: &str, assignee_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM task_assignees WHERE task_id = ?1 AND assignee_id = ?2",
+            params![task_id, assignee_id],
+        )?;
+        Ok(())
+    }
+
+    fn query_assignments(&self, filter: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Vec<TaskAssignment>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT ta.task_id, ta.assignee_id, a.name, ta.allocation, t.title, t.start_date, t.end_date
+             FROM task_assignees ta
+             JOIN assignees a ON ta.assignee_id = a.id
+             JOIN tasks t ON ta.task_id = t.id
+             WHERE {}
+             ORDER BY a.name, t.start_date",
+            filter
+        ))?;
+
+        let assignments = stmt.query_map(params, |row| {
+            Ok(TaskAssignment {
+                task_id: row.get(0)?,
+                assignee_id: row.get(1)?,
+                assignee_name: row.get(2)?,
+                allocation: row.get(3)?,
+                task_title: row.get(4)?,
+                start_date: row.get(5)?,
+                end_date: row.get(6)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(assignments)
+    }
+
+    pub fn get_task_assignments(&self, task_id: &str) -> Result<Vec<TaskAssignment>> {
+        self.query_assignments("ta.task_id = ?1", params![task_id])
+    }
+
+    // Assignments on open tasks whose span overlaps the given window
+    pub fn get_assignments_between(&self, start_date: &str, end_date: &str) -> Result<Vec<TaskAssignment>> {
+        self.query_assignments(
+            "t.status NOT IN ('completed', 'cancelled')
+               AND COALESCE(t.start_date, t.end_date) <= ?2
+               AND COALESCE(t.end_date, t.start_date) >= ?1",
+            params![start_date, end_date],
+        )
+    }
+
+    // Calendar operations
+    pub fn create_calendar(&self, name: &str, work_week: &[chrono::Weekday], use_jp_holidays: bool) -> Result<Calendar> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+
+        conn.execute(
+            "INSERT INTO calendars (id, name, work_week, use_jp_holidays, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, name, work_week_to_json(work_week)?, use_jp_holidays as i32, created_at],
+        )?;
+
+        Ok(Calendar {
+            id,
+            name: name.to_string(),
+            work_week: work_week.to_vec(),
+            use_jp_holidays,
+            created_at,
+        })
+    }
+
+    pub fn get_calendars(&self) -> Result<Vec<Calendar>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name, work_week, use_jp_holidays, created_at FROM calendars ORDER BY created_at")?;
+
+        let calendars = stmt.query_map([], calendar_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(calendars)
+    }
+
+    pub fn update_calendar(&self, id: &str, name: &str, work_week: &[chrono::Weekday], use_jp_holidays: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE calendars SET name = ?1, work_week = ?2, use_jp_holidays = ?3 WHERE id = ?4",
+            params![name, work_week_to_json(work_week)?, use_jp_holidays as i32, id],
+        )?;
+        Ok(())
+    }
+
+    // Projects using the calendar fall back to the default one
+    pub fn delete_calendar(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE projects SET calendar_id = NULL WHERE calendar_id = ?1", params![id])?;
+        conn.execute("DELETE FROM calendar_holidays WHERE calendar_id = ?1", params![id])?;
+        conn.execute("DELETE FROM calendars WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn set_project_calendar(&self, project_id: &str, calendar_id: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE projects SET calendar_id = ?1 WHERE id = ?2",
+            params![calendar_id, project_id],
+        )?;
+        Ok(())
+    }
+
+    // Adding a holiday on an existing date renames it
+    pub fn add_holidays(&self, calendar_id: &str, holidays: &[Holiday]) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for holiday in holidays {
+            tx.execute(
+                "INSERT INTO calendar_holidays (calendar_id, date, name) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(calendar_id, date) DO UPDATE SET name = excluded.name",
+                params![calendar_id, holiday.date, holiday.name],
+            )?;
+        }
+        tx.commit()?;
+        Ok(holidays.len())
+    }
+
+    pub fn delete_holiday(&self, calendar_id: &str, date: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM calendar_holidays WHERE calendar_id = ?1 AND date = ?2",
+            params![calendar_id, date],
+        )?;
+        Ok(())
+    }
+
+    // Holidays stored for the calendar (built-in Japanese holidays are not included)
+    pub fn get_holidays(&self, calendar_id: &str) -> Result<Vec<Holiday>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT date, name FROM calendar_holidays WHERE calendar_id = ?1 ORDER BY date")?;
+
+        let holidays = stmt.query_map(params![calendar_id], |row| {
+            Ok(Holiday {
+                date: row.get(0)?,
+                name: row.get(1)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(holidays)
+    }
+
+    // The calendar and its stored holidays; unknown ids and projects without a calendar use the default
+    pub fn load_calendar(&self, calendar_id: Option<&str>) -> Result<(Calendar, Vec<Holiday>)> {
+        let id = calendar_id.unwrap_or(DEFAULT_CALENDAR_ID);
+        let calendar = {
+            let conn = self.conn.lock().unwrap();
+            let found = conn.query_row(
+                "SELECT id, name, work_week, use_jp_holidays, created_at FROM calendars WHERE id = ?1",
+                params![id],
+                calendar_from_row,
+            );
+            match found {
+                Ok(calendar) => calendar,
+                Err(rusqlite::Error::QueryReturnedNoRows) => conn.query_row(
+                    "SELECT id, name, work_week, use_jp_holidays, created_at FROM calendars WHERE id = ?1",
+                    params![DEFAULT_CALENDAR_ID],
+                    calendar_from_row,
+                )?,
+                Err(e) => return Err(e),
+            }
+        };
+        let holidays = self.get_holidays(&calendar.id)?;
+        Ok((calendar, holidays))
+    }
+
+    pub fn load_project_calendar(&self, project_id: &str) -> Result<(Calendar, Vec<Holiday>)> {
+        let calendar_id: Option<String> = {
+            let conn = self.conn.lock().unwrap();
+            let mut stmt = conn.prepare("SELECT calendar_id FROM projects WHERE id = ?1")?;
+            let mut rows = stmt.query(params![project_id])?;
+            match rows.next()? {
+                Some(row) => row.get(0)?,
+                None => None,
+            }
+        };
+        self.load_calendar(calendar_id.as_deref())
+    }
+
+    // Custom field operations
+    pub fn create_custom_field(&self, project_id: &str, name: &str, field_type: FieldType, options: &[String]) -> Result<CustomField> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+        let order_index: i32 = conn.query_row(
+            "SELECT COALESCE(MAX(order_index), -1) + 1 FROM custom_fields WHERE project_id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+
+        conn.execute(
+            "INSERT INTO custom_fields (id, project_id, name, field_type, options, order_index, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![id, project_id, name, field_type.as_str(), options_to_json(options)?, order_index, created_at],
+        )?;
+
+        Ok(CustomField {
+            id,
+            project_id: project_id.to_string(),
+            name: name.to_string(),
+            field_type,
+            options: options.to_vec(),
+            order_index,
+            created_at,
+        })
+    }
+
+    pub fn get_custom_fields(&self, project_id: &str) -> Result<Vec<CustomField>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, name, field_type, options, order_index, created_at FROM custom_fields WHERE project_id = ?1 ORDER BY order_index",
+        )?;
+
+        let fields = stmt.query_map(params![project_id], custom_field_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(fields)
+    }
+
+    pub fn get_custom_field(&self, id: &str) -> Result<Option<CustomField>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, name, field_type, options, order_index, created_at FROM custom_fields WHERE id = ?1",
+        )?;
+
+        let mut rows = stmt.query(params![id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(custom_field_from_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    // The type is fixed once created so stored values stay valid
+    pub fn update_custom_field(&self, id: &str, name: &str, options: &[String]) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE custom_fields SET name = ?1, options = ?2 WHERE id = ?3",
+            params![name, options_to_json(options)?, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_custom_field(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM task_field_values WHERE field_id = ?1", params![id])?;
+        tx.execute("DELETE FROM custom_fields WHERE id = ?1", params![id])?;
+        tx.commit()
+    }
+
+    // `None` clears the value
+    pub fn set_task_field_value(&self, task_id: &str, field_id: &str, value: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        match value {
+            Some(value) => conn.execute(
+                "INSERT INTO task_field_values (task_id, field_id, value) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(task_id, field_id) DO UPDATE SET value = excluded.value",
+                params![task_id, field_id, value],
+            )?,
+            None => conn.execute(
+                "DELETE FROM task_field_values WHERE task_id = ?1 AND field_id = ?2",
+                params![task_id, field_id],
+            )?,
+        };
+        Ok(())
+    }
+
+    // Values for every task in the project
+    pub fn get_task_field_values(&self, project_id: &str) -> Result<Vec<TaskFieldValue>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT v.task_id, v.field_id, v.value FROM task_field_values v JOIN tasks t ON t.id = v.task_id WHERE t.project_id = ?1",
+        )?;
+
+        let values = stmt.query_map(params![project_id], |row| {
+            Ok(TaskFieldValue {
+                task_id: row.get(0)?,
+                field_id: row.get(1)?,
+                value: row.get(2)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(values)
+    }
+
+    // Tag operations
+    pub fn create_tag(&self, name: &str, color: &str) -> Result<Tag> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+
+        conn.execute(
+            "INSERT INTO tags (id, name, color, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![id, name, color, created_at],
+        )?;
+
+        Ok(Tag {
+            id,
+            name: name.to_string(),
+            color: color.to_string(),
+            created_at,
+        })
+    }
+
+    pub fn get_all_tags(&self) -> Result<Vec<Tag>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name, color, created_at FROM tags ORDER BY name")?;
+
+        let tags = stmt.query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(tags)
+    }
+
+    pub fn update_tag(&self, id: &str, name: &str, color: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE tags SET name = ?1, color = ?2 WHERE id = ?3",
+            params![name, color, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_tag(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM task_tags WHERE tag_id = ?1", params![id])?;
+        tx.execute("DELETE FROM todo_tags WHERE tag_id = ?1", params![id])?;
+        tx.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+        tx.commit()
+    }
+
+    pub fn tag_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES (?1, ?2)",
+            params![task_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn untag_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM task_tags WHERE task_id = ?1 AND tag_id = ?2",
+            params![task_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn tag_todo(&self, todo_id: &str, tag_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) VALUES (?1, ?2)",
+            params![todo_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn untag_todo(&self, todo_id: &str, tag_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM todo_tags WHERE todo_id = ?1 AND tag_id = ?2",
+            params![todo_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    // Tag links for every task in the project
+    pub fn get_task_tag_links(&self, project_id: &str) -> Result<Vec<TagLink>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT tk.task_id, tk.tag_id FROM task_tags tk JOIN tasks t ON t.id = tk.task_id WHERE t.project_id = ?1",
+        )?;
+
+        let links = stmt.query_map(params![project_id], |row| {
+            Ok(TagLink {
+                item_id: row.get(0)?,
+                tag_id: row.get(1)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(links)
+    }
+
+    // Todos carrying the tag directly or through their linked task, optionally within a date range
+    pub fn get_todos_by_tag(&self, tag_id: &str, start_date: Option<&str>, end_date: Option<&str>) -> Result<Vec<DailyTodoWithTask>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE (EXISTS (SELECT 1 FROM todo_tags tt WHERE tt.todo_id = dt.id AND tt.tag_id = ?1)
+                    OR EXISTS (SELECT 1 FROM task_tags tk WHERE tk.task_id = dt.task_id AND tk.tag_id = ?1))
+               AND (?2 IS NULL OR dt.date >= ?2) AND (?3 IS NULL OR dt.date <= ?3)
+             ORDER BY dt.date DESC, dt.completed, dt.order_index, dt.created_at",
+            TODO_WITH_TASK_SELECT
+        ))?;
+
+        let todos = stmt.query_map(params![tag_id, start_date, end_date], todo_with_task_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(todos)
+    }
+
+    // Saved views
+    pub fn create_saved_view(&self, name: &str, query: &TaskQuery) -> Result<SavedView> {
+        let conn = self.conn.lock().unwrap();
+        let id = Uuid::new_v4().to_string();
+        let created_at = now_timestamp();
+        let order_index: i32 = conn.query_row(
+            "SELECT COALESCE(MAX(order_index), -1) + 1 FROM saved_views",
+            [],
+            |row| row.get(0),
+        )?;
+
+        conn.execute(
+            "INSERT INTO saved_views (id, name, query, order_index, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, name, task_query_to_json(query)?, order_index, created_at],
+        )?;
+
+        Ok(SavedView {
+            id,
+            name: name.to_string(),
+            query: query.clone(),
+            order_index,
+            created_at,
+        })
+    }
+
+    pub fn get_saved_views(&self) -> Result<Vec<SavedView>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name, query, order_index, created_at FROM saved_views ORDER BY order_index")?;
+
+        let views = stmt.query_map([], saved_view_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(views)
+    }
+
+    pub fn get_saved_view(&self, id: &str) -> Result<Option<SavedView>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name, query, order_index, created_at FROM saved_views WHERE id = ?1")?;
+
+        let mut rows = stmt.query(params![id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(saved_view_from_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn update_saved_view(&self, id: &str, name: &str, query: &TaskQuery) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE saved_views SET name = ?1, query = ?2 WHERE id = ?3",
+            params![name, task_query_to_json(query)?, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn reorder_saved_views(&self, ids: &[String]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for (index, id) in ids.iter().enumerate() {
+            tx.execute("UPDATE saved_views SET order_index = ?1 WHERE id = ?2", params![index as i32, id])?;
         }
+        tx.commit()
+    }
+
+    pub fn delete_saved_view(&self, id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM saved_views WHERE id = ?1", params![id])?;
+        Ok(())
     }
 
-    pub fn update_project(&self, id: &str, name: &str, description: Option<&str>, start_date: Option<&str>, end_date: Option<&str>) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "UPDATE projects SET name = ?1, description = ?2, start_date = ?3, end_date = ?4 WHERE id = ?5",
-            params![name, description, start_date, end_date, id],
-        )?;
-        Ok(())
-    }
+    // Full-text search. Whitespace-separated terms must all match.
+    pub fn search(&self, query: &str, filters: &SearchFilters) -> Result<Vec<SearchHit>> {
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let use_fts = terms.iter().all(|t| t.chars().count() >= TRIGRAM_MIN_CHARS);
+
+        let mut conditions = Vec::new();
+        let mut values: Vec<String> = Vec::new();
+        let select = if use_fts {
+            let phrase = terms
+                .iter()
+                .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            conditions.push("search_index MATCH ?".to_string());
+            values.push(phrase);
+            SEARCH_SELECT
+                .replace("{rank}", "bm25(search_index, 0.0, 0.0, 10.0, 1.0)")
+                .replace("{snippet}", "snippet(search_index, -1, '【', '】', '…', 32)")
+        } else {
+            for term in &terms {
+                conditions.push("(s.title LIKE ? OR s.body LIKE ?)".to_string());
+                let pattern = format!("%{}%", term);
+                values.push(pattern.clone());
+                values.push(pattern);
+            }
+            SEARCH_SELECT.replace("{rank}", "0.0").replace("{snippet}", "NULL")
+        };
+
+        let kinds: Vec<&String> = filters.kinds.iter().filter(|k| !k.is_empty()).collect();
+        if !kinds.is_empty() {
+            conditions.push(format!("s.kind IN ({})", vec!["?"; kinds.len()].join(", ")));
+            values.extend(kinds.into_iter().cloned());
+        }
+        if let Some(ref project_id) = filters.project_id {
+            conditions.push("COALESCE(p.id, tp.id) = ?".to_string());
+            values.push(project_id.clone());
+        }
+        if let Some(ref date_from) = filters.date_from {
+            conditions.push("hit_date >= ?".to_string());
+            values.push(date_from.clone());
+        }
+        if let Some(ref date_to) = filters.date_to {
+            conditions.push("hit_date <= ?".to_string());
+            values.push(date_to.clone());
+        }
+
+        let sql = format!(
+            "{} WHERE {} ORDER BY 5, hit_date DESC LIMIT {}",
+            select,
+            conditions.join(" AND "),
+            filters.limit.unwrap_or(SEARCH_DEFAULT_LIMIT).max(1)
+        );
 
-    pub fn delete_project(&self, id: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
-        Ok(())
+        let mut stmt = conn.prepare(&sql)?;
+        let hits = stmt.query_map(params_from_iter(values.iter()), |row| {
+            let title: String = row.get(2)?;
+            let body: String = row.get(3)?;
+            let snippet = match row.get::<_, Option<String>>(5)? {
+                Some(snippet) => snippet,
+                None => like_snippet(&body, terms[0])
+                    .or_else(|| like_snippet(&title, terms[0]))
+                    .unwrap_or_else(|| title.clone()),
+            };
+            Ok(SearchHit {
+                kind: row.get(0)?,
+                id: row.get(1)?,
+                title,
+                snippet,
+                rank: row.get(4)?,
+                project_id: row.get(6)?,
+                project_name: row.get(7)?,
+                task_id: row.get(8)?,
+                task_title: row.get(9)?,
+                date: row.get(10)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+
+        Ok(hits)
     }
 
-    // Task CRUD operations
-    pub fn create_task(&self, project_id: &str, parent_id: Option<&str>, title: &str, description: Option<&str>, status: &str, priority: i32, start_date: Option<&str>, end_date: Option<&str>) -> Result<Task> {
-        let conn = self.conn.lock().unwrap();
+    // Baseline operations
+    pub fn create_baseline(&self, project_id: &str, name: &str) -> Result<Baseline> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
         let id = Uuid::new_v4().to_string();
-        let created_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-        
-        // Get max order_index for the parent
-        let order_index: i32 = conn.query_row(
-            "SELECT COALESCE(MAX(order_index), -1) + 1 FROM tasks WHERE project_id = ?1 AND parent_id IS ?2",
-            params![project_id, parent_id],
-            |row| row.get(0),
-        )?;
+        let created_at = now_timestamp();
 
-        conn.execute(
-            "INSERT INTO tasks (id, project_id, parent_id, title, description, status, priority, start_date, end_date, progress, order_index, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0, ?10, ?11)",
-            params![id, project_id, parent_id, title, description, status, priority, start_date, end_date, order_index, created_at],
+        tx.execute(
+            "INSERT INTO baselines (id, project_id, name, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![id, project_id, name, created_at],
+        )?;
+        tx.execute(
+            "INSERT INTO baseline_tasks (baseline_id, task_id, title, start_date, end_date, progress)
+             SELECT ?1, id, title, start_date, end_date, progress FROM tasks WHERE project_id = ?2",
+            params![id, project_id],
         )?;
+        tx.commit()?;
 
-        Ok(Task {
+        Ok(Baseline {
             id,
             project_id: project_id.to_string(),
-            parent_id: parent_id.map(|s| s.to_string()),
-            title: title.to_string(),
-            description: description.map(|s| s.to_string()),
-            status: status.to_string(),
-            priority,
-            start_date: start_date.map(|s| s.to_string()),
-            end_date: end_date.map(|s| s.to_string()),
-            progress: 0,
-            order_index,
+            name: name.to_string(),
             created_at,
         })
     }
 
-    pub fn get_tasks_by_project(&self, project_id: &str) -> Result<Vec<Task>> {
+    pub fn get_baselines(&self, project_id: &str) -> Result<Vec<Baseline>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, project_id, parent_id, title, description, status, priority, start_date, end_date, progress, order_index, created_at FROM tasks WHERE project_id = ?1 ORDER BY order_index"
+            "SELECT id, project_id, name, created_at FROM baselines WHERE project_id = ?1 ORDER BY created_at DESC",
         )?;
 
-        let tasks = stmt.query_map(params![project_id], |row| {
-            Ok(Task {
+        let baselines = stmt.query_map(params![project_id], |row| {
+            Ok(Baseline {
                 id: row.get(0)?,
                 project_id: row.get(1)?,
-                parent_id: row.get(2)?,
-                title: row.get(3)?,
-                description: row.get(4)?,
-                status: row.get(5)?,
-                priority: row.get(6)?,
-                start_date: row.get(7)?,
-                end_date: row.get(8)?,
-                progress: row.get(9)?,
-                order_index: row.get(10)?,
-                created_at: row.get(11)?,
+                name: row.get(2)?,
+                created_at: row.get(3)?,
             })
         })?.collect::<Result<Vec<_>>>()?;
 
-        Ok(tasks)
+        Ok(baselines)
     }
 
-    pub fn update_task(&self, id: &str, title: &str, description: Option<&str>, status: &str, priority: i32, start_date: Option<&str>, end_date: Option<&str>, progress: i32) -> Result<()> {
+    pub fn get_baseline(&self, id: &str) -> Result<Option<Baseline>> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "UPDATE tasks SET title = ?1, description = ?2, status = ?3, priority = ?4, start_date = ?5, end_date = ?6, progress = ?7 WHERE id = ?8",
-            params![title, description, status, priority, start_date, end_date, progress, id],
-        )?;
-        Ok(())
-    }
+        let mut stmt = conn.prepare("SELECT id, project_id, name, created_at FROM baselines WHERE id = ?1")?;
 
-    pub fn update_task_dates(&self, id: &str, start_date: Option<&str>, end_date: Option<&str>) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "UPDATE tasks SET start_date = ?1, end_date = ?2 WHERE id = ?3",
-            params![start_date, end_date, id],
-        )?;
-        Ok(())
+        let mut rows = stmt.query(params![id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(Baseline {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                name: row.get(2)?,
+                created_at: row.get(3)?,
+            })),
+            None => Ok(None),
+        }
     }
 
-    pub fn delete_task(&self, id: &str) -> Result<()> {
+    pub fn rename_baseline(&self, id: &str, name: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM tasks WHERE id = ?1", params![id])?;
+        conn.execute("UPDATE baselines SET name = ?1 WHERE id = ?2", params![name, id])?;
         Ok(())
     }
 
-    // Daily TODO CRUD operations
-    pub fn create_daily_todo(&self, task_id: Option<&str>, title: &str, date: &str, memo: Option<&str>) -> Result<DailyTodo> {
+    pub fn delete_baseline(&self, id: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        let id = Uuid::new_v4().to_string();
-        let created_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-
-        conn.execute(
-            "INSERT INTO daily_todos (id, task_id, title, date, completed, memo, created_at) VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6)",
-            params![id, task_id, title, date, memo, created_at],
-        )?;
-
-        Ok(DailyTodo {
-            id,
-            task_id: task_id.map(|s| s.to_string()),
-            title: title.to_string(),
-            date: date.to_string(),
-            completed: false,
-            memo: memo.map(|s| s.to_string()),
-            created_at,
-        })
+        conn.execute("DELETE FROM baseline_tasks WHERE baseline_id = ?1", params![id])?;
+        conn.execute("DELETE FROM baselines WHERE id = ?1", params![id])?;
+        Ok(())
     }
 
-    pub fn get_todos_by_date(&self, date: &str) -> Result<Vec<DailyTodoWithTask>> {
+    pub fn get_baseline_tasks(&self, baseline_id: &str) -> Result<Vec<BaselineTask>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT dt.id, dt.task_id, dt.title, dt.date, dt.completed, dt.memo, dt.created_at, t.title as task_title, p.name as project_name
-             FROM daily_todos dt
-             LEFT JOIN tasks t ON dt.task_id = t.id
-             LEFT JOIN projects p ON t.project_id = p.id
-             WHERE dt.date = ?1
-             ORDER BY dt.completed, dt.created_at"
+            "SELECT bt.baseline_id, bt.task_id, bt.title, bt.start_date, bt.end_date, bt.progress
+             FROM baseline_tasks bt
+             LEFT JOIN tasks t ON bt.task_id = t.id
+             WHERE bt.baseline_id = ?1
+             ORDER BY t.order_index IS NULL, t.order_index, bt.title",
         )?;
 
-        let todos = stmt.query_map(params![date], |row| {
-            Ok(DailyTodoWithTask {
-                id: row.get(0)?,
+        let tasks = stmt.query_map(params![baseline_id], |row| {
+            Ok(BaselineTask {
+                baseline_id: row.get(0)?,
                 task_id: row.get(1)?,
                 title: row.get(2)?,
-                date: row.get(3)?,
-                completed: row.get::<_, i32>(4)? == 1,
-                memo: row.get(5)?,
-                created_at: row.get(6)?,
-                task_title: row.get(7)?,
-                project_name: row.get(8)?,
+                start_date: row.get(3)?,
+                end_date: row.get(4)?,
+                progress: row.get(5)?,
             })
         })?.collect::<Result<Vec<_>>>()?;
 
-        Ok(todos)
+        Ok(tasks)
     }
 
-    pub fn toggle_todo(&self, id: &str) -> Result<bool> {
+    // Bulk operations. Each call is one transaction; an item that fails is rolled back on its
+    // own through a savepoint and reported, the rest are still applied.
+    fn bulk_apply<F>(&self, ids: &[String], mut apply: F) -> Result<Vec<BulkItemResult>>
+    where
+        F: FnMut(&Connection, &str) -> std::result::Result<(), BulkError>,
+    {
+        let mut conn = self.conn.lock().unwrap();
+        let mut tx = conn.transaction()?;
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            let sp = tx.savepoint()?;
+            let error = match apply(&sp, id) {
+                Ok(()) => None,
+                Err(BulkError::Rejected(message)) => Some(message),
+                Err(BulkError::Db(rusqlite::Error::QueryReturnedNoRows)) => Some("Not found".to_string()),
+                Err(BulkError::Db(e)) => Some(e.to_string()),
+            };
+            if error.is_none() {
+                sp.commit()?;
+            }
+            results.push(BulkItemResult { id: id.clone(), ok: error.is_none(), error });
+        }
+        tx.commit()?;
+        Ok(results)
+    }
+
+    pub fn bulk_set_task_status(&self, ids: &[String], status: TaskStatus) -> Result<Vec<BulkItemResult>> {
+        self.bulk_apply(ids, |conn, id| {
+            let (old_status, old_progress): (TaskStatus, i32) = conn.query_row(
+                "SELECT status, progress FROM tasks WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            if old_status != status {
+                // Same progress rule as completing a task through todo sync
+                let progress = Self::progress_after_status(conn, id, status, old_progress)?;
+                conn.execute("UPDATE tasks SET status = ?1, progress = ?2 WHERE id = ?3", params![status, progress, id])?;
+                Self::record_activity(conn, id, "status", Some(old_status.as_str()), Some(status.as_str()))?;
+                if progress != old_progress {
+                    Self::record_activity(conn, id, "progress", Some(&old_progress.to_string()), Some(&progress.to_string()))?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub fn bulk_set_task_priority(&self, ids: &[String], priority: i32) -> Result<Vec<BulkItemResult>> {
+        self.bulk_apply(ids, |conn, id| {
+            if conn.execute("UPDATE tasks SET priority = ?1 WHERE id = ?2", params![priority, id])? == 0 {
+                return Err(BulkError::Db(rusqlite::Error::QueryReturnedNoRows));
+            }
+            Ok(())
+        })
+    }
+
+    // `shift` maps a task to its new (start, end) dates or a reason it can't be moved
+    pub fn bulk_shift_task_dates<F>(&self, ids: &[String], shift: F) -> Result<Vec<BulkItemResult>>
+    where
+        F: Fn(&Task) -> std::result::Result<(Option<String>, Option<String>), String>,
+    {
+        self.bulk_apply(ids, |conn, id| {
+            let task = conn.query_row(&format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS), params![id], task_from_row)?;
+            let (start_date, end_date) = shift(&task).map_err(BulkError::Rejected)?;
+            conn.execute(
+                "UPDATE tasks SET start_date = ?1, end_date = ?2 WHERE id = ?3",
+                params![start_date, end_date, id],
+            )?;
+            Self::record_date_changes(
+                conn,
+                id,
+                (task.start_date.as_deref(), task.end_date.as_deref()),
+                (start_date.as_deref(), end_date.as_deref()),
+            )?;
+            Self::roll_up_summary_dates(conn, id)?;
+            Ok(())
+        })
+    }
+
+    // Each task goes with its subtree; ids inside an earlier item's subtree count as deleted
+    pub fn bulk_delete_tasks(&self, ids: &[String]) -> Result<Vec<BulkItemResult>> {
+        let mut removed = HashSet::new();
+        self.bulk_apply(ids, |conn, id| {
+            if removed.contains(id) {
+                return Ok(());
+            }
+            conn.query_row("SELECT 1 FROM tasks WHERE id = ?1", params![id], |_| Ok(()))?;
+            let mut stmt = conn.prepare(&format!("{} SELECT id FROM target", TASK_SUBTREE))?;
+            let subtree = stmt.query_map(params![id], |row| row.get(0))?.collect::<Result<Vec<String>>>()?;
+            Self::delete_task_row(conn, id)?;
+            removed.extend(subtree);
+            Ok(())
+        })
+    }
+
+    // Tasks already on the date's list are reported instead of being added twice
+    pub fn bulk_add_tasks_to_todos(&self, ids: &[String], date: &str) -> Result<Vec<BulkItemResult>> {
+        self.bulk_apply(ids, |conn, id| {
+            let title: String = conn.query_row("SELECT title FROM tasks WHERE id = ?1", params![id], |row| row.get(0))?;
+            let planned: bool = conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM daily_todos WHERE task_id = ?1 AND date = ?2)",
+                params![id, date],
+                |row| row.get(0),
+            )?;
+            if planned {
+                return Err(BulkError::Rejected("Already in this day's todos".to_string()));
+            }
+            Self::insert_daily_todo(conn, Some(id), &title, date, None)?;
+            Ok(())
+        })
+    }
+
+    // Settings
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
         let conn = self.conn.lock().unwrap();
-        let current: i32 = conn.query_row(
-            "SELECT completed FROM daily_todos WHERE id = ?1",
-            params![id],
-            |row| row.get(0),
-        )?;
-        let new_value = if current == 1 { 0 } else { 1 };
-        conn.execute(
-            "UPDATE daily_todos SET completed = ?1 WHERE id = ?2",
-            params![new_value, id],
-        )?;
-        Ok(new_value == 1)
+        let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
+        let mut rows = stmt.query(params![key])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
     }
 
-    pub fn update_todo_memo(&self, id: &str, memo: Option<&str>) -> Result<()> {
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "UPDATE daily_todos SET memo = ?1 WHERE id = ?2",
-            params![memo, id],
+            "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
         )?;
         Ok(())
     }
+}
 
-    pub fn delete_todo(&self, id: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM daily_todos WHERE id = ?1", params![id])?;
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open() -> Database {
+        Database::new(":memory:").unwrap()
     }
 
-    pub fn add_task_to_todo(&self, task_id: &str, date: &str) -> Result<DailyTodo> {
-        let conn = self.conn.lock().unwrap();
-        
-        // Get task title
-        let title: String = conn.query_row(
-            "SELECT title FROM tasks WHERE id = ?1",
-            params![task_id],
-            |row| row.get(0),
-        )?;
+    fn task(db: &Database, project_id: &str, parent_id: Option<&str>, title: &str) -> Task {
+        db.create_task(project_id, parent_id, title, None, TaskStatus::Pending, 0, Some("2026-01-05"), Some("2026-01-09"), TaskKind::Task).unwrap()
+    }
 
-        drop(conn);
-        
-        self.create_daily_todo(Some(task_id), &title, date, None)
+    #[test]
+    fn deleting_a_project_removes_its_tasks_and_attachments() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let parent = task(&db, &project.id, None, "parent");
+        let child = task(&db, &project.id, Some(&parent.id), "child");
+        db.add_attachment(&child.id, "a.txt", 1, "text/plain", "hash-a").unwrap();
+        db.add_checklist_item(&child.id, "step").unwrap();
+        let todo = db.add_task_to_todo(&child.id, "2026-01-05").unwrap();
+
+        db.delete_project(&project.id).unwrap();
+
+        assert!(db.get_task(&parent.id).unwrap().is_none());
+        assert!(db.get_task(&child.id).unwrap().is_none());
+        assert!(db.get_attachments(&child.id).unwrap().is_empty());
+        assert!(db.get_checklist_items(&child.id).unwrap().is_empty());
+        assert!(db.prune_attachments().unwrap().is_empty());
+        // Todos outlive their task, like ON DELETE SET NULL
+        let todos = db.get_todos_by_date("2026-01-05").unwrap();
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].id, todo.id);
+        assert_eq!(todos[0].task_id, None);
+    }
+
+    #[test]
+    fn deleting_a_task_removes_its_subtree() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let parent = task(&db, &project.id, None, "parent");
+        let child = task(&db, &project.id, Some(&parent.id), "child");
+        let grandchild = task(&db, &project.id, Some(&child.id), "grandchild");
+        let sibling = task(&db, &project.id, None, "sibling");
+        db.add_attachment(&grandchild.id, "a.txt", 1, "text/plain", "hash-a").unwrap();
+        db.add_attachment(&sibling.id, "b.txt", 1, "text/plain", "hash-b").unwrap();
+
+        db.delete_task(&child.id).unwrap();
+
+        assert!(db.get_task(&parent.id).unwrap().is_some());
+        assert!(db.get_task(&grandchild.id).unwrap().is_none());
+        assert_eq!(db.prune_attachments().unwrap(), HashSet::from(["hash-b".to_string()]));
+    }
+
+    fn text_values(values: &[Value]) -> Vec<&str> {
+        values
+            .iter()
+            .map(|v| match v {
+                Value::Text(s) => s.as_str(),
+                _ => panic!("expected a text value, got {:?}", v),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn task_query_conditions_bind_every_filter() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let query = TaskQuery {
+            statuses: vec![TaskStatus::Pending, TaskStatus::InProgress],
+            kinds: vec![TaskKind::Milestone],
+            due_within_days: Some(7),
+            fields: vec![FieldFilter { field_id: "f".to_string(), op: FieldOp::Eq, value: "3".to_string() }],
+            ..Default::default()
+        };
+
+        let (sql, values) = task_query_conditions(&query, today);
+
+        assert!(sql.contains("status IN (?, ?)"));
+        assert!(sql.contains("kind IN (?)"));
+        assert_eq!(sql.matches('?').count(), values.len());
+        assert_eq!(
+            text_values(&values),
+            ["pending", "in_progress", "milestone", "2024-06-10", "2024-06-17", "f", "3", "3"]
+        );
+    }
+
+    #[test]
+    fn task_query_conditions_clamp_due_within_days() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        for (days, until) in [(-5, "2024-06-10"), (i64::MAX, "2124-05-17")] {
+            let query = TaskQuery { due_within_days: Some(days), ..Default::default() };
+            let (_, values) = task_query_conditions(&query, today);
+            assert_eq!(text_values(&values), ["2024-06-10", until]);
+        }
+    }
+
+    #[test]
+    fn query_tasks_filters_sorts_and_pages() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        task(&db, &project.id, None, "Write spec");
+        let second = task(&db, &project.id, None, "Review spec");
+        let milestone = task(&db, &project.id, None, "Release");
+        db.set_task_kind(&milestone.id, TaskKind::Milestone).unwrap();
+        db.bulk_set_task_priority(std::slice::from_ref(&second.id), 2).unwrap();
+
+        let query = TaskQuery {
+            text: Some(" spec ".to_string()),
+            sort: TaskSort::Priority,
+            descending: true,
+            limit: Some(1),
+            ..Default::default()
+        };
+        let page = db.query_tasks(&query).unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), [second.id.as_str()]);
+
+        let query = TaskQuery { kinds: vec![TaskKind::Milestone], ..Default::default() };
+        let page = db.query_tasks(&query).unwrap();
+        assert_eq!(page.tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), [milestone.id.as_str()]);
+    }
+
+    fn todo_ids(db: &Database, date: &str) -> Vec<String> {
+        db.get_todos_by_date(date).unwrap().into_iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn reorder_todos_puts_unlisted_todos_after_the_listed_ones() {
+        let db = open();
+        let a = db.create_daily_todo(None, "a", "2024-06-10", None).unwrap();
+        let b = db.create_daily_todo(None, "b", "2024-06-10", None).unwrap();
+        let c = db.create_daily_todo(None, "c", "2024-06-10", None).unwrap();
+        let other = db.create_daily_todo(None, "other", "2024-06-11", None).unwrap();
+
+        db.reorder_todos("2024-06-10", &[c.id.clone(), other.id.clone(), a.id.clone()]).unwrap();
+
+        assert_eq!(todo_ids(&db, "2024-06-10"), [c.id, a.id, b.id]);
+        // Ids from another day are left where they are
+        assert_eq!(todo_ids(&db, "2024-06-11"), [other.id]);
+    }
+
+    #[test]
+    fn carry_over_move_is_idempotent() {
+        let db = open();
+        let open_todo = db.create_daily_todo(None, "open", "2024-06-10", None).unwrap();
+        let done = db.create_daily_todo(None, "done", "2024-06-10", None).unwrap();
+        db.toggle_todo(&done.id).unwrap();
+
+        let carried = db.carry_over_todos("2024-06-10", "2024-06-11", CarryOverMode::Move).unwrap();
+        assert_eq!(carried.len(), 1);
+        assert_eq!(carried[0].id, open_todo.id);
+        assert_eq!(carried[0].carry_count, 1);
+        assert_eq!(todo_ids(&db, "2024-06-10"), [done.id]);
+        assert_eq!(todo_ids(&db, "2024-06-11"), [open_todo.id.as_str()]);
+
+        assert!(db.carry_over_todos("2024-06-10", "2024-06-11", CarryOverMode::Move).unwrap().is_empty());
+        assert_eq!(todo_ids(&db, "2024-06-11"), [open_todo.id]);
+    }
+
+    #[test]
+    fn carry_over_copy_is_idempotent() {
+        let db = open();
+        let original = db.create_daily_todo(None, "open", "2024-06-10", None).unwrap();
+
+        let carried = db.carry_over_todos("2024-06-10", "2024-06-11", CarryOverMode::Copy).unwrap();
+        assert_eq!(carried.len(), 1);
+        assert_eq!(carried[0].carried_from.as_deref(), Some(original.id.as_str()));
+        assert!(db.carry_over_todos("2024-06-10", "2024-06-11", CarryOverMode::Copy).unwrap().is_empty());
+        assert_eq!(todo_ids(&db, "2024-06-10"), [original.id]);
+        assert_eq!(todo_ids(&db, "2024-06-11"), [carried[0].id.as_str()]);
+
+        // Only the latest copy in the chain moves on
+        let again = db.carry_over_past_todos("2024-06-12", CarryOverMode::Copy).unwrap();
+        assert_eq!(again.len(), 1);
+        assert_eq!(again[0].carried_from.as_deref(), Some(carried[0].id.as_str()));
+        assert_eq!(again[0].carry_count, 2);
+    }
+
+    #[test]
+    fn bulk_apply_rolls_back_only_the_failing_item() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let a = task(&db, &project.id, None, "a");
+        let b = task(&db, &project.id, None, "b");
+        let ids = [a.id.clone(), "missing".to_string(), b.id.clone()];
+
+        let results = db
+            .bulk_apply(&ids, |conn, id| {
+                conn.query_row("SELECT 1 FROM tasks WHERE id = ?1", params![id], |_| Ok(()))?;
+                conn.execute("UPDATE tasks SET title = 'renamed' WHERE id = ?1", params![id])?;
+                if id == b.id {
+                    return Err(BulkError::Rejected("no".to_string()));
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        let outcome: Vec<(bool, Option<&str>)> = results.iter().map(|r| (r.ok, r.error.as_deref())).collect();
+        assert_eq!(outcome, [(true, None), (false, Some("Not found")), (false, Some("no"))]);
+        assert_eq!(db.get_task(&a.id).unwrap().unwrap().title, "renamed");
+        assert_eq!(db.get_task(&b.id).unwrap().unwrap().title, "b");
+    }
+
+    #[test]
+    fn bulk_delete_tasks_counts_listed_descendants_as_deleted() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let parent = task(&db, &project.id, None, "parent");
+        let child = task(&db, &project.id, Some(&parent.id), "child");
+        let grandchild = task(&db, &project.id, Some(&child.id), "grandchild");
+
+        let results = db.bulk_delete_tasks(&[parent.id.clone(), grandchild.id.clone()]).unwrap();
+
+        assert!(results.iter().all(|r| r.ok));
+        for id in [&parent.id, &child.id, &grandchild.id] {
+            assert!(db.get_task(id).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn every_task_write_bumps_the_version_once() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let created = task(&db, &project.id, None, "a");
+        assert_eq!(created.version, 1);
+
+        let patch = TaskPatch { title: Some("b".to_string()), ..Default::default() };
+        let patched = match db.patch_task(&created.id, &patch, 1).unwrap() {
+            PatchOutcome::Applied(task) => task,
+            _ => panic!("patch against the current version was not applied"),
+        };
+        assert_eq!(patched.version, 2);
+
+        // Writes outside patch_task are versioned by the trigger too
+        db.bulk_set_task_priority(std::slice::from_ref(&created.id), 3).unwrap();
+        match db.patch_task(&created.id, &patch, 2).unwrap() {
+            PatchOutcome::Stale(current) => assert_eq!((current.version, current.priority), (3, 3)),
+            _ => panic!("patch against an old version was applied"),
+        }
+        assert!(matches!(db.patch_task("missing", &patch, 1).unwrap(), PatchOutcome::NotFound));
+    }
+
+    #[test]
+    fn changes_since_include_rows_written_at_or_after_the_cursor() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let at_cursor = task(&db, &project.id, None, "at cursor");
+        let before = task(&db, &project.id, None, "before");
+        let removed = task(&db, &project.id, None, "removed");
+        let todo = db.create_daily_todo(None, "todo", "2024-06-10", None).unwrap();
+        db.delete_task(&removed.id).unwrap();
+        {
+            // Bumping the version keeps the touch triggers from overwriting the timestamps
+            let conn = db.conn.lock().unwrap();
+            let stamp = |table: &str, id: &str, at: &str| {
+                let bump = if table == "daily_todos" { "" } else { ", version = version + 1" };
+                conn.execute(&format!("UPDATE {} SET updated_at = ?1{} WHERE id = ?2", table, bump), params![at, id]).unwrap();
+            };
+            stamp("projects", &project.id, "2024-06-10T11:00:00.000Z");
+            stamp("tasks", &at_cursor.id, "2024-06-10T12:00:00.000Z");
+            stamp("tasks", &before.id, "2024-06-10T11:59:59.999Z");
+            stamp("daily_todos", &todo.id, "2024-06-11T00:00:00.000Z");
+            conn.execute("UPDATE deleted_records SET deleted_at = '2024-06-10T12:00:00.000Z' WHERE id = ?1", params![removed.id]).unwrap();
+            conn.execute("INSERT INTO deleted_records (kind, id, deleted_at) VALUES ('todo', 'old', '2024-06-09T00:00:00.000Z')", []).unwrap();
+        }
+
+        let changes = db.get_changes_since("2024-06-10T12:00:00.000Z").unwrap();
+
+        assert!(changes.projects.is_empty());
+        assert_eq!(changes.tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), [at_cursor.id.as_str()]);
+        assert_eq!(changes.todos.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), [todo.id.as_str()]);
+        let deleted: Vec<(&str, &str)> = changes.deleted.iter().map(|d| (d.kind.as_str(), d.id.as_str())).collect();
+        assert_eq!(deleted, [("task", removed.id.as_str())]);
+        assert!(changes.until.as_str() > "2024-06-11T00:00:00.000Z");
+    }
+
+    #[test]
+    fn untimestamped_writes_are_touched_by_the_triggers() {
+        let db = open();
+        let todo = db.create_daily_todo(None, "todo", "2024-06-10", None).unwrap();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute("UPDATE daily_todos SET updated_at = '2024-01-01T00:00:00.000Z' WHERE id = ?1", params![todo.id])
+            .unwrap();
+
+        db.update_todo_memo(&todo.id, Some("memo")).unwrap();
+
+        let changes = db.get_changes_since("2024-01-01T00:00:00.001Z").unwrap();
+        assert_eq!(changes.todos.len(), 1);
+        assert!(changes.todos[0].updated_at.ends_with('Z'));
+        assert_ne!(changes.todos[0].updated_at, "2024-01-01T00:00:00.000Z");
     }
-}
 
+    #[test]
+    fn deleting_a_tagged_todo_removes_its_tag_links() {
+        let db = open();
+        let tag = db.create_tag("urgent", "#ff0000").unwrap();
+        let todo = db.create_daily_todo(None, "todo", "2024-06-10", None).unwrap();
+        db.tag_todo(&todo.id, &tag.id).unwrap();
+
+        db.delete_todo(&todo.id).unwrap();
+
+        let links: i64 = db
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM todo_tags WHERE todo_id = ?1", params![todo.id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(links, 0);
+        assert!(db.get_todos_by_tag(&tag.id, None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn deleting_a_todo_unlinks_its_time_entries() {
+        let db = open();
+        let todo = db.create_daily_todo(None, "todo", "2024-06-10", None).unwrap();
+        let entry = db.start_timer(Some(&todo.id), None, None).unwrap();
+
+        db.delete_todo(&todo.id).unwrap();
+
+        let running = db.get_running_timer().unwrap().unwrap();
+        assert_eq!(running.id, entry.id);
+        assert_eq!(running.todo_id, None);
+    }
+
+    #[test]
+    fn deleting_a_carried_todo_clears_the_copy_link() {
+        let db = open();
+        let original = db.create_daily_todo(None, "open", "2024-06-10", None).unwrap();
+        let copy = db.carry_over_todos("2024-06-10", "2024-06-11", CarryOverMode::Copy).unwrap().remove(0);
+
+        db.delete_todo(&original.id).unwrap();
+
+        let todos = db.get_todos_by_date("2024-06-11").unwrap();
+        assert_eq!(todos[0].id, copy.id);
+        assert_eq!(todos[0].carried_from, None);
+        assert_eq!(todos[0].carry_count, 1);
+    }
+
+    #[test]
+    fn a_deleted_recurring_todo_is_not_materialized_again() {
+        let db = open();
+        let recurring = db
+            .create_recurring_todo(None, "standup", None, &RecurrenceRule::EveryNDays { interval: 1 }, "2024-06-10", None)
+            .unwrap();
+        let todos = db.get_todos_by_date("2024-06-10").unwrap();
+        assert_eq!(todos.len(), 1);
+
+        db.delete_todo(&todos[0].id).unwrap();
+
+        assert!(db.get_todos_by_date("2024-06-10").unwrap().is_empty());
+        let todo_id: Option<String> = db
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT todo_id FROM recurring_todo_instances WHERE recurring_id = ?1 AND date = '2024-06-10'",
+                params![recurring.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(todo_id, None);
+    }
+
+    #[test]
+    fn completing_through_a_todo_keeps_checklist_progress() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let checked = task(&db, &project.id, None, "checklist");
+        let item = db.add_checklist_item(&checked.id, "one").unwrap();
+        db.add_checklist_item(&checked.id, "two").unwrap();
+        db.toggle_checklist_item(&item.id).unwrap();
+        db.set_progress_from_checklist(&checked.id, true).unwrap();
+        let plain = task(&db, &project.id, None, "plain");
+
+        for task in [&checked, &plain] {
+            let todo = db.add_task_to_todo(&task.id, "2024-06-10").unwrap();
+            db.set_todo_final(&todo.id, true).unwrap();
+            db.toggle_todo(&todo.id).unwrap();
+        }
+
+        let checked = db.get_task(&checked.id).unwrap().unwrap();
+        assert_eq!((checked.status, checked.progress), (TaskStatus::Completed, 50));
+        let plain = db.get_task(&plain.id).unwrap().unwrap();
+        assert_eq!((plain.status, plain.progress), (TaskStatus::Completed, 100));
+    }
+
+    #[test]
+    fn bulk_completion_sets_progress_like_todo_sync() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let plain = task(&db, &project.id, None, "plain");
+        let checked = task(&db, &project.id, None, "checklist");
+        db.add_checklist_item(&checked.id, "one").unwrap();
+        db.set_progress_from_checklist(&checked.id, true).unwrap();
+
+        db.bulk_set_task_status(&[plain.id.clone(), checked.id.clone()], TaskStatus::Completed).unwrap();
+
+        assert_eq!(db.get_task(&plain.id).unwrap().unwrap().progress, 100);
+        assert_eq!(db.get_task(&checked.id).unwrap().unwrap().progress, 0);
+    }
+
+    fn search_ids(db: &Database, query: &str) -> Vec<String> {
+        db.search(query, &SearchFilters::default()).unwrap().into_iter().map(|hit| hit.id).collect()
+    }
+
+    #[test]
+    fn title_matches_rank_above_body_matches() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let in_body = db
+            .create_task(&project.id, None, "misc", Some("numbers for the quarterly review"), TaskStatus::Pending, 0, None, None, TaskKind::Task)
+            .unwrap();
+        let in_title = task(&db, &project.id, None, "quarterly report");
+        task(&db, &project.id, None, "unrelated");
+
+        assert_eq!(search_ids(&db, "quarterly"), vec![in_title.id, in_body.id]);
+    }
+
+    #[test]
+    fn short_terms_fall_back_to_substring_matching() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let ui = task(&db, &project.id, None, "UI review");
+        let todo = db.create_daily_todo(None, "議事録をまとめる", "2026-01-05", None).unwrap();
+        task(&db, &project.id, None, "deploy");
+
+        let hits = db.search("ui", &SearchFilters::default()).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].id.as_str(), hits[0].rank), (ui.id.as_str(), 0.0));
+        assert_eq!(hits[0].snippet, "【UI】 review");
+        assert_eq!(search_ids(&db, "議事"), vec![todo.id]);
+        // Every term has to match
+        assert!(search_ids(&db, "ui 議事").is_empty());
+    }
+
+    #[test]
+    fn snippets_mark_the_match_within_its_context() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        db.create_task(&project.id, None, "release", Some("move the deadline to friday"), TaskStatus::Pending, 0, None, None, TaskKind::Task)
+            .unwrap();
+
+        let hits = db.search("deadline", &SearchFilters::default()).unwrap();
+        assert_eq!(hits[0].snippet, "move the 【deadline】 to friday");
+
+        let text = format!("{}needle{}", "a".repeat(30), "b".repeat(30));
+        assert_eq!(
+            like_snippet(&text, "NEEDLE").unwrap(),
+            format!("…{}【needle】{}…", "a".repeat(20), "b".repeat(20))
+        );
+        assert_eq!(like_snippet("short needle", "needle").unwrap(), "short 【needle】");
+        assert_eq!(like_snippet("short", "needle"), None);
+    }
+
+    #[test]
+    fn todos_carry_their_own_and_their_tasks_tags() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let spec = task(&db, &project.id, None, "spec");
+        let client = db.create_tag("client", "#ff0000").unwrap();
+        let urgent = db.create_tag("urgent", "#00ff00").unwrap();
+        db.tag_task(&spec.id, &client.id).unwrap();
+        let linked = db.create_daily_todo(Some(&spec.id), "write spec", "2024-06-10", None).unwrap();
+        db.tag_todo(&linked.id, &urgent.id).unwrap();
+        db.tag_todo(&linked.id, &client.id).unwrap();
+        let later = db.create_daily_todo(None, "call client", "2024-06-12", None).unwrap();
+        db.tag_todo(&later.id, &client.id).unwrap();
+        db.create_daily_todo(None, "untagged", "2024-06-10", None).unwrap();
+
+        let mut tags = db.get_todos_by_date("2024-06-10").unwrap().into_iter().find(|t| t.id == linked.id).unwrap().tags;
+        tags.sort();
+        assert_eq!(tags, ["client", "urgent"]);
+
+        let ids = |todos: Vec<DailyTodoWithTask>| todos.into_iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(db.get_todos_by_tag(&client.id, None, None).unwrap()), [later.id.as_str(), linked.id.as_str()]);
+        assert_eq!(ids(db.get_todos_by_tag(&client.id, None, Some("2024-06-11")).unwrap()), [linked.id.as_str()]);
+        assert_eq!(ids(db.get_todos_by_tag(&urgent.id, Some("2024-06-11"), None).unwrap()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn tasks_filter_by_tag_until_the_tag_is_deleted() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let tagged = task(&db, &project.id, None, "tagged");
+        task(&db, &project.id, None, "plain");
+        let tag = db.create_tag("client", "#ff0000").unwrap();
+        db.tag_task(&tagged.id, &tag.id).unwrap();
+        // Tagging twice keeps a single link
+        db.tag_task(&tagged.id, &tag.id).unwrap();
+        let todo = db.create_daily_todo(None, "todo", "2024-06-10", None).unwrap();
+        db.tag_todo(&todo.id, &tag.id).unwrap();
+
+        let query = TaskQuery { tag_id: Some(tag.id.clone()), ..Default::default() };
+        let page = db.query_tasks(&query).unwrap();
+        assert_eq!(page.tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), [tagged.id.as_str()]);
+        assert_eq!(db.get_task_tag_links(&project.id).unwrap().len(), 1);
+
+        db.delete_tag(&tag.id).unwrap();
+        assert_eq!(db.query_tasks(&query).unwrap().total, 0);
+        assert!(db.get_task_tag_links(&project.id).unwrap().is_empty());
+        assert!(db.get_todos_by_date("2024-06-10").unwrap()[0].tags.is_empty());
+    }
+
+    #[test]
+    fn deleting_a_comment_removes_its_whole_thread() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let spec = task(&db, &project.id, None, "spec");
+        let root = db.add_task_comment(&spec.id, None, Some("sato"), "first").unwrap();
+        let reply = db.add_task_comment(&spec.id, Some(&root.id), None, "reply").unwrap();
+        db.add_task_comment(&spec.id, Some(&reply.id), None, "reply to reply").unwrap();
+        let other = db.add_task_comment(&spec.id, None, None, "another thread").unwrap();
+
+        db.update_task_comment(&reply.id, "edited").unwrap();
+        let edited = db.get_task_comment(&reply.id).unwrap().unwrap();
+        assert_eq!((edited.body.as_str(), edited.parent_id.as_deref()), ("edited", Some(root.id.as_str())));
+        assert!(edited.edited_at.is_some());
+
+        db.delete_task_comment(&root.id).unwrap();
+        let left: Vec<String> = db.get_task_comments(&spec.id).unwrap().into_iter().map(|c| c.id).collect();
+        assert_eq!(left, [other.id]);
+    }
+
+    #[test]
+    fn task_edits_log_title_status_progress_and_date_changes() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let spec = task(&db, &project.id, None, "spec");
+        let activity = |db: &Database| {
+            let mut changes: Vec<(String, Option<String>, Option<String>)> =
+                db.get_task_activity(&spec.id).unwrap().into_iter().map(|a| (a.field, a.old_value, a.new_value)).collect();
+            changes.sort();
+            changes
+        };
+        let change = |field: &str, old: &str, new: &str| (field.to_string(), Some(old.to_string()), Some(new.to_string()));
+
+        db.update_task(&spec.id, "spec v2", None, TaskStatus::InProgress, 3, Some("2026-01-05"), Some("2026-01-12"), 20).unwrap();
+        assert_eq!(
+            activity(&db),
+            [
+                change("end_date", "2026-01-09", "2026-01-12"),
+                change("progress", "0", "20"),
+                change("status", "pending", "in_progress"),
+                change("title", "spec", "spec v2"),
+            ]
+        );
+
+        db.update_task_dates(&spec.id, Some("2026-01-06"), Some("2026-01-12")).unwrap();
+        assert_eq!(activity(&db).len(), 5);
+        assert!(activity(&db).contains(&change("start_date", "2026-01-05", "2026-01-06")));
+    }
+
+    #[test]
+    fn reordering_a_checklist_leaves_other_tasks_items_alone() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let spec = task(&db, &project.id, None, "spec");
+        let other = task(&db, &project.id, None, "other");
+        let a = db.add_checklist_item(&spec.id, "a").unwrap();
+        let b = db.add_checklist_item(&spec.id, "b").unwrap();
+        let c = db.add_checklist_item(&spec.id, "c").unwrap();
+        let x = db.add_checklist_item(&other.id, "x").unwrap();
+        assert_eq!((a.order_index, c.order_index, x.order_index), (0, 2, 0));
+
+        db.reorder_checklist_items(&spec.id, &[c.id.clone(), x.id.clone(), a.id.clone(), b.id.clone()]).unwrap();
+
+        let titles = |task_id: &str| db.get_checklist_items(task_id).unwrap().into_iter().map(|i| (i.title, i.order_index)).collect::<Vec<_>>();
+        assert_eq!(titles(&spec.id), [("c".to_string(), 0), ("a".to_string(), 2), ("b".to_string(), 3)]);
+        assert_eq!(titles(&other.id), [("x".to_string(), 0)]);
+    }
+
+    #[test]
+    fn checklist_progress_follows_checked_items_once_enabled() {
+        let db = open();
+        let project = db.create_project("P", None, None, None).unwrap();
+        let spec = task(&db, &project.id, None, "spec");
+        let progress = |db: &Database| db.get_task(&spec.id).unwrap().unwrap().progress;
+        let items: Vec<ChecklistItem> = ["a", "b", "c", "d"].iter().map(|t| db.add_checklist_item(&spec.id, t).unwrap()).collect();
+
+        // Without the flag the checklist is informational only
+        db.toggle_checklist_item(&items[0].id).unwrap();
+        assert_eq!(progress(&db), 0);
+
+        db.set_progress_from_checklist(&spec.id, true).unwrap();
+        assert_eq!(progress(&db), 25);
+        assert!(db.toggle_checklist_item(&items[1].id).unwrap());
+        assert_eq!(progress(&db), 50);
+        db.delete_checklist_item(&items[2].id).unwrap();
+        assert_eq!(progress(&db), 66);
+        db.add_checklist_item(&spec.id, "e").unwrap();
+        assert_eq!(progress(&db), 50);
+        assert!(!db.toggle_checklist_item(&items[0].id).unwrap());
+        assert_eq!(progress(&db), 25);
+
+        let logged = db.get_task_activity(&spec.id).unwrap().into_iter().filter(|a| a.field == "progress").count();
+        assert_eq!(logged, 5);
+
+        // Turning the flag off keeps the last computed value
+        db.set_progress_from_checklist(&spec.id, false).unwrap();
+        db.toggle_checklist_item(&items[0].id).unwrap();
+        assert_eq!(progress(&db), 25);
+    }
+}
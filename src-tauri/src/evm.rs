@@ -0,0 +1,242 @@
+use crate::calendar::WorkCalendar;
+use crate::db::{parse_date, Task};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Earned value metrics, all amounts in estimated hours
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct EarnedValue {
+    // Budget at completion
+    pub bac: f64,
+    // Planned value
+    pub pv: f64,
+    // Earned value
+    pub ev: f64,
+    // Actual cost
+    pub ac: f64,
+    pub spi: Option<f64>,
+    pub cpi: Option<f64>,
+    // Estimate at completion
+    pub eac: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskEarnedValue {
+    pub task_id: String,
+    pub parent_id: Option<String>,
+    pub title: String,
+    #[serde(flatten)]
+    pub value: EarnedValue,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectEarnedValue {
+    pub project_id: String,
+    pub as_of: String,
+    #[serde(flatten)]
+    pub total: EarnedValue,
+    pub tasks: Vec<TaskEarnedValue>,
+}
+
+impl EarnedValue {
+    fn add(&mut self, other: &EarnedValue) {
+        self.bac += other.bac;
+        self.pv += other.pv;
+        self.ev += other.ev;
+        self.ac += other.ac;
+    }
+
+    fn finish(mut self) -> Self {
+        self.spi = if self.pv > 0.0 { Some(self.ev / self.pv) } else { None };
+        self.cpi = if self.ac > 0.0 { Some(self.ev / self.ac) } else { None };
+        self.eac = match self.cpi {
+            Some(cpi) if cpi > 0.0 => Some(self.bac / cpi),
+            // Nothing earned yet: assume the remaining work goes to plan
+            _ if self.ac > 0.0 => Some(self.ac + self.bac - self.ev),
+            _ => None,
+        };
+        self
+    }
+}
+
+// Share of the task's schedule that should be done by `as_of`, linear over its working days
+fn planned_fraction(task: &Task, as_of: NaiveDate, calendar: &WorkCalendar) -> f64 {
+    let (start, end) = match (parse_date(&task.start_date), parse_date(&task.end_date)) {
+        (Some(start), Some(end)) => (start, end.max(start)),
+        (Some(date), None) | (None, Some(date)) => (date, date),
+        (None, None) => return 0.0,
+    };
+    if as_of < start {
+        0.0
+    } else if as_of >= end {
+        1.0
+    } else {
+        let total = calendar.working_days_between(start, end);
+        if total == 0 {
+            return 1.0;
+        }
+        calendar.working_days_between(start, as_of) as f64 / total as f64
+    }
+}
+
+// Leaves carry their own estimate; summary tasks roll up their children and any time logged against them directly
+fn rollup(
+    task: &Task,
+    children: &HashMap<Option<&str>, Vec<&Task>>,
+    actual_hours: &HashMap<&str, f64>,
+    as_of: NaiveDate,
+    calendar: &WorkCalendar,
+    out: &mut Vec<TaskEarnedValue>,
+) -> EarnedValue {
+    let own_actual = actual_hours.get(task.id.as_str()).copied().unwrap_or(0.0);
+    let index = out.len();
+    out.push(TaskEarnedValue {
+        task_id: task.id.clone(),
+        parent_id: task.parent_id.clone(),
+        title: task.title.clone(),
+        value: EarnedValue::default(),
+    });
+
+    let mut value = match children.get(&Some(task.id.as_str())) {
+        Some(kids) => {
+            let mut sum = EarnedValue::default();
+            for child in kids {
+                sum.add(&rollup(child, children, actual_hours, as_of, calendar, out));
+            }
+            sum.ac += own_actual;
+            sum
+        }
+        None => {
+            let bac = task.estimate_hours.unwrap_or(0.0);
+            EarnedValue {
+                bac,
+                pv: bac * planned_fraction(task, as_of, calendar),
+                ev: bac * f64::from(task.progress.clamp(0, 100)) / 100.0,
+                ac: own_actual,
+                ..Default::default()
+            }
+        }
+    };
+    value = value.finish();
+    out[index].value = value.clone();
+    value
+}
+
+pub fn project_earned_value(
+    project_id: &str,
+    tasks: &[Task],
+    actual_seconds: &[(String, i64)],
+    as_of: NaiveDate,
+    calendar: &WorkCalendar,
+) -> ProjectEarnedValue {
+    let mut children: HashMap<Option<&str>, Vec<&Task>> = HashMap::new();
+    for task in tasks {
+        children.entry(task.parent_id.as_deref()).or_default().push(task);
+    }
+    let actual_hours: HashMap<&str, f64> = actual_seconds
+        .iter()
+        .map(|(id, secs)| (id.as_str(), *secs as f64 / 3600.0))
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut total = EarnedValue::default();
+    for root in children.get(&None).cloned().unwrap_or_default() {
+        total.add(&rollup(root, &children, &actual_hours, as_of, calendar, &mut rows));
+    }
+
+    ProjectEarnedValue {
+        project_id: project_id.to_string(),
+        as_of: as_of.format("%Y-%m-%d").to_string(),
+        total: total.finish(),
+        tasks: rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{TaskKind, TaskStatus};
+
+    fn task(id: &str, parent_id: Option<&str>, estimate_hours: Option<f64>, progress: i32, dates: Option<(&str, &str)>) -> Task {
+        Task {
+            id: id.to_string(),
+            project_id: "p".to_string(),
+            parent_id: parent_id.map(|s| s.to_string()),
+            title: id.to_string(),
+            description: None,
+            status: TaskStatus::InProgress,
+            priority: 0,
+            start_date: dates.map(|(start, _)| start.to_string()),
+            end_date: dates.map(|(_, end)| end.to_string()),
+            progress,
+            order_index: 0,
+            estimate_hours,
+            kind: TaskKind::Task,
+            progress_from_checklist: false,
+            created_at: String::new(),
+            version: 1,
+            updated_at: String::new(),
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn summary_tasks_roll_up_their_children_and_own_time() {
+        let tasks = vec![
+            task("parent", None, None, 0, None),
+            task("a", Some("parent"), Some(10.0), 50, Some(("2024-06-03", "2024-06-07"))),
+            task("b", Some("parent"), Some(6.0), 0, Some(("2024-06-10", "2024-06-14"))),
+        ];
+        let actual = vec![("a".to_string(), 4 * 3600), ("parent".to_string(), 3600)];
+        let as_of = NaiveDate::from_ymd_opt(2024, 6, 5).unwrap();
+
+        let result = project_earned_value("p", &tasks, &actual, as_of, &WorkCalendar::standard());
+
+        let ids: Vec<&str> = result.tasks.iter().map(|t| t.task_id.as_str()).collect();
+        assert_eq!(ids, ["parent", "a", "b"]);
+
+        // Three of the five working days have passed
+        let a = &result.tasks[1].value;
+        assert_close(a.pv, 6.0);
+        assert_close(a.ev, 5.0);
+        assert_close(a.ac, 4.0);
+        assert_close(a.spi.unwrap(), 5.0 / 6.0);
+        assert_close(a.eac.unwrap(), 8.0);
+
+        let parent = &result.tasks[0].value;
+        assert_close(parent.bac, 16.0);
+        assert_close(parent.ac, 5.0);
+        assert_close(parent.cpi.unwrap(), 1.0);
+        assert_close(result.total.eac.unwrap(), 16.0);
+    }
+
+    #[test]
+    fn ratios_are_empty_until_there_is_something_to_divide_by() {
+        let tasks = vec![task("a", None, Some(8.0), 0, None)];
+        let as_of = NaiveDate::from_ymd_opt(2024, 6, 5).unwrap();
+
+        let result = project_earned_value("p", &tasks, &[], as_of, &WorkCalendar::standard());
+
+        assert_close(result.total.pv, 0.0);
+        assert_eq!(result.total.spi, None);
+        assert_eq!(result.total.cpi, None);
+        assert_eq!(result.total.eac, None);
+    }
+
+    #[test]
+    fn time_without_progress_projects_the_remaining_budget() {
+        let tasks = vec![task("a", None, Some(8.0), 0, Some(("2024-06-03", "2024-06-07")))];
+        let actual = vec![("a".to_string(), 2 * 3600)];
+        let as_of = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+
+        let result = project_earned_value("p", &tasks, &actual, as_of, &WorkCalendar::standard());
+
+        assert_close(result.total.pv, 8.0);
+        assert_eq!(result.total.cpi, Some(0.0));
+        assert_close(result.total.eac.unwrap(), 10.0);
+    }
+}
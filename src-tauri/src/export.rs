@@ -0,0 +1,92 @@
+use crate::db::{CustomField, Task, TaskFieldValue};
+use crate::fields;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+const TASK_HEADERS: [&str; 11] = [
+    "id", "parent_id", "title", "kind", "status", "priority", "start_date", "end_date", "progress", "estimate_hours", "description",
+];
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// task id -> field id -> stored value
+fn values_by_task(values: &[TaskFieldValue]) -> HashMap<&str, HashMap<&str, &str>> {
+    let mut map: HashMap<&str, HashMap<&str, &str>> = HashMap::new();
+    for v in values {
+        map.entry(v.task_id.as_str()).or_default().insert(v.field_id.as_str(), v.value.as_str());
+    }
+    map
+}
+
+// One row per task with a column per custom field
+pub fn tasks_csv(tasks: &[Task], fields: &[CustomField], values: &[TaskFieldValue]) -> String {
+    let values = values_by_task(values);
+    let mut lines = Vec::new();
+
+    let headers = TASK_HEADERS
+        .iter()
+        .map(|h| h.to_string())
+        .chain(fields.iter().map(|f| csv_escape(&f.name)));
+    lines.push(headers.collect::<Vec<_>>().join(","));
+
+    for task in tasks {
+        let mut row = vec![
+            task.id.clone(),
+            task.parent_id.clone().unwrap_or_default(),
+            task.title.clone(),
+            task.kind.as_str().to_string(),
+            task.status.as_str().to_string(),
+            task.priority.to_string(),
+            task.start_date.clone().unwrap_or_default(),
+            task.end_date.clone().unwrap_or_default(),
+            task.progress.to_string(),
+            task.estimate_hours.map(|h| h.to_string()).unwrap_or_default(),
+            task.description.clone().unwrap_or_default(),
+        ];
+        let task_values = values.get(task.id.as_str());
+        for field in fields {
+            let value = task_values.and_then(|v| v.get(field.id.as_str())).copied().unwrap_or("");
+            row.push(value.to_string());
+        }
+        lines.push(row.iter().map(|v| csv_escape(v)).collect::<Vec<_>>().join(","));
+    }
+
+    lines.join("\n")
+}
+
+// Tasks with their custom field values keyed by field name
+pub fn tasks_json(tasks: &[Task], fields: &[CustomField], values: &[TaskFieldValue]) -> String {
+    let values = values_by_task(values);
+    let rows: Vec<serde_json::Value> = tasks
+        .iter()
+        .map(|task| {
+            let mut custom = serde_json::Map::new();
+            let task_values = values.get(task.id.as_str());
+            for field in fields {
+                let value = task_values
+                    .and_then(|v| v.get(field.id.as_str()))
+                    .map(|v| fields::to_json(field.field_type, v))
+                    .unwrap_or(serde_json::Value::Null);
+                custom.insert(field.name.clone(), value);
+            }
+            let mut row = serde_json::to_value(task).unwrap_or_default();
+            row["custom_fields"] = serde_json::Value::Object(custom);
+            row
+        })
+        .collect();
+    serde_json::to_string_pretty(&rows).unwrap_or_default()
+}
@@ -0,0 +1,132 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Text,
+    Number,
+    Date,
+    // One of the field's options
+    Enum,
+    Checkbox,
+}
+
+impl FieldType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldType::Text => "text",
+            FieldType::Number => "number",
+            FieldType::Date => "date",
+            FieldType::Enum => "enum",
+            FieldType::Checkbox => "checkbox",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(FieldType::Text),
+            "number" => Some(FieldType::Number),
+            "date" => Some(FieldType::Date),
+            "enum" => Some(FieldType::Enum),
+            "checkbox" => Some(FieldType::Checkbox),
+            _ => None,
+        }
+    }
+}
+
+// Checks a raw value against the field type and returns the form stored in the database
+pub fn normalize_value(field_type: FieldType, options: &[String], raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    match field_type {
+        FieldType::Text => Ok(raw.to_string()),
+        FieldType::Number => raw
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(|n| n.to_string())
+            .ok_or_else(|| format!("\"{}\" is not a number", raw)),
+        FieldType::Date => NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .map_err(|_| format!("\"{}\" is not a YYYY-MM-DD date", raw)),
+        FieldType::Enum => options
+            .iter()
+            .find(|o| o.as_str() == raw)
+            .cloned()
+            .ok_or_else(|| format!("\"{}\" is not one of: {}", raw, options.join(", "))),
+        FieldType::Checkbox => match raw.to_lowercase().as_str() {
+            "true" | "1" | "yes" => Ok("true".to_string()),
+            "false" | "0" | "no" | "" => Ok("false".to_string()),
+            _ => Err(format!("\"{}\" is not a checkbox value", raw)),
+        },
+    }
+}
+
+// Stored value as JSON, typed for numbers and checkboxes
+pub fn to_json(field_type: FieldType, value: &str) -> serde_json::Value {
+    match field_type {
+        FieldType::Number => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        FieldType::Checkbox => serde_json::Value::Bool(value == "true"),
+        _ => serde_json::Value::String(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(field_type: FieldType, raw: &str) -> Result<String, String> {
+        normalize_value(field_type, &["Low".to_string(), "High".to_string()], raw)
+    }
+
+    #[test]
+    fn numbers_are_stored_in_canonical_form() {
+        assert_eq!(normalize(FieldType::Number, " 1.50 "), Ok("1.5".to_string()));
+        assert_eq!(normalize(FieldType::Number, "-2"), Ok("-2".to_string()));
+        assert!(normalize(FieldType::Number, "abc").is_err());
+        assert!(normalize(FieldType::Number, "NaN").is_err());
+        assert!(normalize(FieldType::Number, "inf").is_err());
+    }
+
+    #[test]
+    fn dates_must_be_valid_calendar_days() {
+        assert_eq!(normalize(FieldType::Date, "2024-02-29"), Ok("2024-02-29".to_string()));
+        assert!(normalize(FieldType::Date, "2023-02-29").is_err());
+        assert!(normalize(FieldType::Date, "2024/02/01").is_err());
+    }
+
+    #[test]
+    fn enums_only_accept_their_options() {
+        assert_eq!(normalize(FieldType::Enum, "High"), Ok("High".to_string()));
+        assert_eq!(normalize(FieldType::Enum, "high"), Err("\"high\" is not one of: Low, High".to_string()));
+    }
+
+    #[test]
+    fn checkboxes_accept_common_spellings() {
+        for raw in ["true", "YES", "1"] {
+            assert_eq!(normalize(FieldType::Checkbox, raw), Ok("true".to_string()));
+        }
+        for raw in ["false", "No", "0", ""] {
+            assert_eq!(normalize(FieldType::Checkbox, raw), Ok("false".to_string()));
+        }
+        assert!(normalize(FieldType::Checkbox, "maybe").is_err());
+    }
+
+    #[test]
+    fn text_is_trimmed() {
+        assert_eq!(normalize(FieldType::Text, "  note "), Ok("note".to_string()));
+    }
+
+    #[test]
+    fn field_types_round_trip_through_their_names() {
+        for field_type in [FieldType::Text, FieldType::Number, FieldType::Date, FieldType::Enum, FieldType::Checkbox] {
+            assert_eq!(FieldType::parse(field_type.as_str()), Some(field_type));
+        }
+        assert_eq!(FieldType::parse("list"), None);
+    }
+}
@@ -1,9 +1,24 @@
+mod attachments;
+mod baseline;
+mod calendar;
 mod db;
 mod commands;
+mod evm;
+mod export;
+mod fields;
+mod recurrence;
+mod report;
+mod suggest;
+mod todo_sync;
+mod workload;
 
-use db::Database;
+use attachments::AttachmentStore;
+use db::{CarryOverMode, Database};
 use std::sync::Arc;
 
+// Setting key for carrying over unfinished todos on start ("move", "copy" or unset)
+const CARRY_OVER_SETTING: &str = "carry_over_mode";
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize database
@@ -16,7 +31,25 @@ pub fn run() {
     let db_path = app_dir.join("data.db");
     let database = Database::new(db_path.to_str().unwrap())
         .expect("Failed to initialize database");
+
+    let carry_over_mode = match database.get_setting(CARRY_OVER_SETTING) {
+        Ok(Some(mode)) if mode == "move" => Some(CarryOverMode::Move),
+        Ok(Some(mode)) if mode == "copy" => Some(CarryOverMode::Copy),
+        _ => None,
+    };
+    if let Some(mode) = carry_over_mode {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        if let Err(e) = database.carry_over_past_todos(&today, mode) {
+            eprintln!("Failed to carry over todos: {}", e);
+        }
+    }
     
+    // Attachment files live next to data.db; clear out any left behind by deleted tasks
+    let store = AttachmentStore::new(app_dir.join("attachments"));
+    if let Err(e) = commands::cleanup_attachments(&database, &store) {
+        eprintln!("Failed to clean up attachments: {:?}", e);
+    }
+
     let db_state = Arc::new(database);
 
     tauri::Builder::default()
@@ -24,24 +57,126 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .manage(db_state)
+        .manage(Arc::new(store))
         .invoke_handler(tauri::generate_handler![
             commands::create_project,
             commands::get_all_projects,
             commands::get_project,
             commands::update_project,
+            commands::patch_project,
             commands::delete_project,
             commands::create_task,
             commands::get_tasks_by_project,
             commands::update_task,
+            commands::patch_task,
+            commands::get_changes_since,
             commands::update_task_dates,
+            commands::update_task_estimate,
+            commands::project_earned_value,
             commands::delete_task,
             commands::create_daily_todo,
             commands::get_todos_by_date,
             commands::toggle_todo,
             commands::update_todo_memo,
+            commands::suggest_todos,
+            commands::set_todo_final,
+            commands::get_todo_sync_rules,
+            commands::set_todo_sync_rules,
+            commands::update_todo_schedule,
+            commands::reorder_todos,
             commands::delete_todo,
             commands::add_task_to_todo,
+            commands::carry_over_todos,
+            commands::create_recurring_todo,
+            commands::get_recurring_todos,
+            commands::update_recurring_todo,
+            commands::delete_recurring_todo,
+            commands::start_timer,
+            commands::stop_timer,
+            commands::get_running_timer,
+            commands::add_time_entry,
+            commands::get_time_entries,
+            commands::delete_time_entry,
+            commands::get_time_by_task,
+            commands::get_time_by_project,
+            commands::get_time_by_day,
+            commands::create_assignee,
+            commands::get_all_assignees,
+            commands::update_assignee,
+            commands::delete_assignee,
+            commands::assign_task,
+            commands::unassign_task,
+            commands::get_task_assignments,
+            commands::get_workload,
+            commands::query_tasks,
+            commands::create_custom_field,
+            commands::get_custom_fields,
+            commands::update_custom_field,
+            commands::delete_custom_field,
+            commands::set_task_field_value,
+            commands::get_task_field_values,
+            commands::export_tasks,
+            commands::create_tag,
+            commands::get_all_tags,
+            commands::update_tag,
+            commands::delete_tag,
+            commands::tag_task,
+            commands::untag_task,
+            commands::tag_todo,
+            commands::untag_todo,
+            commands::get_task_tag_links,
+            commands::get_todos_by_tag,
+            commands::create_saved_view,
+            commands::get_saved_views,
+            commands::update_saved_view,
+            commands::reorder_saved_views,
+            commands::delete_saved_view,
+            commands::evaluate_saved_view,
+            commands::set_task_kind,
+            commands::get_upcoming_milestones,
+            commands::search,
+            commands::bulk_set_task_status,
+            commands::bulk_set_task_priority,
+            commands::bulk_shift_task_dates,
+            commands::bulk_delete_tasks,
+            commands::bulk_add_tasks_to_todos,
+            commands::add_checklist_item,
+            commands::get_checklist_items,
+            commands::update_checklist_item,
+            commands::toggle_checklist_item,
+            commands::reorder_checklist_items,
+            commands::delete_checklist_item,
+            commands::set_progress_from_checklist,
+            commands::attach_file,
+            commands::get_attachments,
+            commands::open_attachment,
+            commands::delete_attachment,
+            commands::backup_data,
+            commands::add_task_comment,
+            commands::update_task_comment,
+            commands::delete_task_comment,
+            commands::get_task_timeline,
+            commands::create_baseline,
+            commands::get_baselines,
+            commands::rename_baseline,
+            commands::delete_baseline,
+            commands::get_baseline_tasks,
+            commands::compare_baseline,
+            commands::create_calendar,
+            commands::get_calendars,
+            commands::update_calendar,
+            commands::delete_calendar,
+            commands::set_project_calendar,
+            commands::add_holiday,
+            commands::delete_holiday,
+            commands::get_holidays,
+            commands::import_holidays,
+            commands::count_working_days,
+            commands::add_working_days,
+            commands::get_setting,
+            commands::set_setting,
             commands::generate_daily_report,
+            commands::generate_period_report,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,109 @@
+use crate::calendar::WorkCalendar;
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+// How often a recurring todo is materialized into daily_todos
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecurrenceRule {
+    // Working days of the default calendar
+    Weekdays,
+    // Specific days of the week, e.g. every Monday
+    Weekly { weekdays: Vec<Weekday> },
+    // First working day of each month
+    FirstBusinessDayOfMonth,
+    // Every `interval` days counted from the rule's start date
+    EveryNDays { interval: u32 },
+}
+
+impl RecurrenceRule {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            RecurrenceRule::Weekly { weekdays } if weekdays.is_empty() => {
+                Err("Weekly recurrence needs at least one weekday".to_string())
+            }
+            RecurrenceRule::EveryNDays { interval: 0 } => {
+                Err("Recurrence interval must be at least 1 day".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn occurs_on(&self, start: NaiveDate, date: NaiveDate, calendar: &WorkCalendar) -> bool {
+        if date < start {
+            return false;
+        }
+        match self {
+            RecurrenceRule::Weekdays => calendar.is_working_day(date),
+            RecurrenceRule::Weekly { weekdays } => weekdays.contains(&date.weekday()),
+            RecurrenceRule::FirstBusinessDayOfMonth => {
+                date == calendar.next_working_day(date.with_day(1).unwrap())
+            }
+            RecurrenceRule::EveryNDays { interval } => {
+                (date - start).num_days() % i64::from(*interval) == 0
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn occurs(rule: &RecurrenceRule, start: &str, on: &str) -> bool {
+        rule.occurs_on(date(start), date(on), &WorkCalendar::standard())
+    }
+
+    #[test]
+    fn weekdays_skip_weekends_and_holidays() {
+        let rule = RecurrenceRule::Weekdays;
+        assert!(occurs(&rule, "2024-07-01", "2024-07-12"));
+        assert!(!occurs(&rule, "2024-07-01", "2024-07-13"));
+        // Marine Day
+        assert!(!occurs(&rule, "2024-07-01", "2024-07-15"));
+    }
+
+    #[test]
+    fn weekly_matches_the_listed_days_only() {
+        let rule = RecurrenceRule::Weekly { weekdays: vec![Weekday::Mon, Weekday::Thu] };
+        assert!(occurs(&rule, "2024-07-01", "2024-07-04"));
+        assert!(!occurs(&rule, "2024-07-01", "2024-07-05"));
+        // Explicit weekdays are kept on holidays
+        assert!(occurs(&rule, "2024-07-01", "2024-07-15"));
+    }
+
+    #[test]
+    fn first_business_day_skips_leading_holidays_and_weekends() {
+        let rule = RecurrenceRule::FirstBusinessDayOfMonth;
+        assert!(!occurs(&rule, "2024-01-01", "2024-01-01"));
+        assert!(occurs(&rule, "2024-01-01", "2024-01-02"));
+        assert!(occurs(&rule, "2024-01-01", "2024-09-02"));
+        assert!(!occurs(&rule, "2024-01-01", "2024-09-03"));
+    }
+
+    #[test]
+    fn every_n_days_counts_from_the_start_date() {
+        let rule = RecurrenceRule::EveryNDays { interval: 3 };
+        assert!(occurs(&rule, "2024-07-01", "2024-07-01"));
+        assert!(occurs(&rule, "2024-07-01", "2024-07-07"));
+        assert!(!occurs(&rule, "2024-07-01", "2024-07-08"));
+    }
+
+    #[test]
+    fn nothing_occurs_before_the_start_date() {
+        for rule in [RecurrenceRule::Weekdays, RecurrenceRule::EveryNDays { interval: 1 }] {
+            assert!(!occurs(&rule, "2024-07-10", "2024-07-09"));
+        }
+    }
+
+    #[test]
+    fn empty_or_zero_rules_are_rejected() {
+        assert!(RecurrenceRule::Weekly { weekdays: Vec::new() }.validate().is_err());
+        assert!(RecurrenceRule::EveryNDays { interval: 0 }.validate().is_err());
+        assert!(RecurrenceRule::EveryNDays { interval: 1 }.validate().is_ok());
+    }
+}
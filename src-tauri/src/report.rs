@@ -0,0 +1,1043 @@
+use crate::db::{DailyTodoWithTask, Milestone, TaskActivity, TaskStatus};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+const NO_PROJECT: &str = "プロジェクトなし";
+const NO_TASK: &str = "単独TODO";
+const NO_TAG: &str = "タグなし";
+const NONE_TEXT: &str = "なし";
+
+// Todos carried over at least this many times are flagged as chronically slipping
+pub const CHRONIC_CARRY_COUNT: i32 = 3;
+
+// Default layout for Markdown period reports. Custom templates can use the same placeholders.
+pub const DEFAULT_PERIOD_TEMPLATE: &str = "# 期間レポート - {{start}} 〜 {{end}}
+
+{{summary}}
+## 完了したタスク
+{{completed}}
+## 日別の完了率
+{{daily}}
+## マイルストーン
+{{milestones}}
+## 繰り返し持ち越されたTODO
+{{slipping}}
+## WBSタスクの変更
+{{changes}}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Html,
+    Text,
+    Json,
+}
+
+// Format-independent report layout shared by all renderers
+pub struct Document {
+    pub title: String,
+    pub sections: Vec<Section>,
+}
+
+pub struct Section {
+    // Placeholder name used by Markdown templates
+    pub key: &'static str,
+    pub heading: Option<String>,
+    pub blocks: Vec<Block>,
+}
+
+pub enum Block {
+    Paragraph(String),
+    Subheading(String),
+    List(Vec<ListItem>),
+    Table { headers: Vec<String>, rows: Vec<Vec<String>> },
+}
+
+pub struct ListItem {
+    pub text: String,
+    pub checked: Option<bool>,
+    pub depth: usize,
+}
+
+impl ListItem {
+    fn new(text: String, checked: Option<bool>, depth: usize) -> Self {
+        ListItem { text, checked, depth }
+    }
+}
+
+pub trait Renderer {
+    fn render_blocks(&self, blocks: &[Block]) -> String;
+    fn render(&self, doc: &Document) -> String;
+}
+
+pub fn renderer_for(format: ReportFormat) -> Option<Box<dyn Renderer>> {
+    match format {
+        ReportFormat::Markdown => Some(Box::new(MarkdownRenderer)),
+        ReportFormat::Html => Some(Box::new(HtmlRenderer)),
+        ReportFormat::Text => Some(Box::new(TextRenderer)),
+        ReportFormat::Json => None,
+    }
+}
+
+// Daily report
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportGrouping {
+    #[default]
+    Flat,
+    // Project, then the WBS parent of the linked task
+    Project,
+    // One group per tag; todos with several tags appear in each
+    Tag,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyReport {
+    pub date: String,
+    pub group_by: ReportGrouping,
+    pub completed: Vec<DailyTodoWithTask>,
+    pub incomplete: Vec<DailyTodoWithTask>,
+    pub memo: Option<String>,
+}
+
+pub fn build_daily_report(date: &str, todos: Vec<DailyTodoWithTask>, memo: &str, group_by: ReportGrouping) -> DailyReport {
+    let (completed, incomplete) = todos.into_iter().partition(|t| t.completed);
+    DailyReport {
+        date: date.to_string(),
+        group_by,
+        completed,
+        incomplete,
+        memo: Some(memo.to_string()).filter(|m| !m.is_empty()),
+    }
+}
+
+fn status_label(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "未着手",
+        TaskStatus::InProgress => "進行中",
+        TaskStatus::Completed => "完了",
+        TaskStatus::Cancelled => "キャンセル",
+    }
+}
+
+fn carry_suffix(carry_count: i32) -> String {
+    match carry_count {
+        0 => String::new(),
+        n if n >= CHRONIC_CARRY_COUNT => format!(" ⚠持ち越し{}回", n),
+        n => format!(" (持ち越し{}回)", n),
+    }
+}
+
+fn todo_line(todo: &DailyTodoWithTask) -> String {
+    let title = match todo.project_name {
+        Some(ref project) => format!("{}: {}", project, todo.title),
+        None => todo.title.clone(),
+    };
+    format!("{}{}", title, carry_suffix(todo.carry_count))
+}
+
+// Todo title followed by the linked task's current status and progress
+fn todo_line_with_task(todo: &DailyTodoWithTask) -> String {
+    let title = match (todo.task_status, todo.task_progress) {
+        (Some(status), Some(progress)) => format!("{} [{} {}%]", todo.title, status_label(status), progress),
+        _ => todo.title.clone(),
+    };
+    format!("{}{}", title, carry_suffix(todo.carry_count))
+}
+
+fn format_hours(seconds: i64) -> String {
+    format!("{:.1}h", seconds as f64 / 3600.0)
+}
+
+fn push_todo(items: &mut Vec<ListItem>, text: String, todo: &DailyTodoWithTask, depth: usize) {
+    // Completed items show the time tracked against them
+    let text = if todo.completed && todo.tracked_seconds > 0 {
+        format!("{} ({})", text, format_hours(todo.tracked_seconds))
+    } else {
+        text
+    };
+    items.push(ListItem::new(text, Some(todo.completed), depth));
+    if let Some(ref m) = todo.memo {
+        if !m.is_empty() {
+            items.push(ListItem::new(m.clone(), None, depth + 1));
+        }
+    }
+}
+
+fn flat_blocks(todos: &[DailyTodoWithTask]) -> Vec<Block> {
+    let mut items = Vec::new();
+    for todo in todos {
+        push_todo(&mut items, todo_line(todo), todo, 0);
+    }
+    list_or_none(items)
+}
+
+// Keyed by parent task id; the title is only used as the heading
+type ParentGroup<'a> = (Option<&'a str>, Option<&'a str>, Vec<&'a DailyTodoWithTask>);
+
+fn grouped_blocks(todos: &[DailyTodoWithTask]) -> Vec<Block> {
+    // Project -> parent task -> todos, keeping first-seen order at each level
+    let mut projects: Vec<(Option<&str>, Vec<ParentGroup>)> = Vec::new();
+    for todo in todos {
+        let project = todo.project_name.as_deref();
+        let parent = todo.parent_task_id.as_deref();
+        let project_idx = match projects.iter().position(|(p, _)| *p == project) {
+            Some(idx) => idx,
+            None => {
+                projects.push((project, Vec::new()));
+                projects.len() - 1
+            }
+        };
+        let parents = &mut projects[project_idx].1;
+        match parents.iter_mut().find(|(p, _, _)| *p == parent) {
+            Some((_, _, group)) => group.push(todo),
+            None => parents.push((parent, todo.parent_task_title.as_deref(), vec![todo])),
+        }
+    }
+
+    let mut blocks = Vec::new();
+    for (project, parents) in projects {
+        blocks.push(Block::Subheading(project.unwrap_or(NO_PROJECT).to_string()));
+        let mut items = Vec::new();
+        for (_, title, group) in parents {
+            let depth = match title {
+                Some(title) => {
+                    items.push(ListItem::new(title.to_string(), None, 0));
+                    1
+                }
+                None => 0,
+            };
+            for todo in group {
+                push_todo(&mut items, todo_line_with_task(todo), todo, depth);
+            }
+        }
+        blocks.push(Block::List(items));
+    }
+    if blocks.is_empty() {
+        blocks.push(Block::Paragraph(NONE_TEXT.to_string()));
+    }
+    blocks
+}
+
+fn tag_blocks(todos: &[DailyTodoWithTask]) -> Vec<Block> {
+    // Tags in first-seen order, untagged todos last
+    let mut tags: Vec<(&str, Vec<&DailyTodoWithTask>)> = Vec::new();
+    let mut untagged = Vec::new();
+    for todo in todos {
+        if todo.tags.is_empty() {
+            untagged.push(todo);
+        }
+        for tag in &todo.tags {
+            match tags.iter_mut().find(|(t, _)| *t == tag.as_str()) {
+                Some((_, group)) => group.push(todo),
+                None => tags.push((tag.as_str(), vec![todo])),
+            }
+        }
+    }
+    if !untagged.is_empty() {
+        tags.push((NO_TAG, untagged));
+    }
+
+    let mut blocks = Vec::new();
+    for (tag, group) in tags {
+        blocks.push(Block::Subheading(tag.to_string()));
+        let mut items = Vec::new();
+        for todo in group {
+            push_todo(&mut items, todo_line(todo), todo, 0);
+        }
+        blocks.push(Block::List(items));
+    }
+    if blocks.is_empty() {
+        blocks.push(Block::Paragraph(NONE_TEXT.to_string()));
+    }
+    blocks
+}
+
+impl DailyReport {
+    pub fn to_document(&self) -> Document {
+        let blocks = |todos: &[DailyTodoWithTask]| match self.group_by {
+            ReportGrouping::Flat => flat_blocks(todos),
+            ReportGrouping::Project => grouped_blocks(todos),
+            ReportGrouping::Tag => tag_blocks(todos),
+        };
+
+        let mut sections = vec![
+            Section {
+                key: "completed",
+                heading: Some("完了したタスク".to_string()),
+                blocks: blocks(&self.completed),
+            },
+            Section {
+                key: "incomplete",
+                heading: Some("未完了のタスク".to_string()),
+                blocks: blocks(&self.incomplete),
+            },
+        ];
+        if let Some(ref memo) = self.memo {
+            sections.push(Section {
+                key: "memo",
+                heading: Some("メモ".to_string()),
+                blocks: vec![Block::Paragraph(memo.clone())],
+            });
+        }
+
+        Document {
+            title: format!("日報 - {}", self.date),
+            sections,
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match renderer_for(format) {
+            Some(renderer) => renderer.render(&self.to_document()),
+            None => serde_json::to_string_pretty(self).unwrap_or_default(),
+        }
+    }
+}
+
+fn list_or_none(items: Vec<ListItem>) -> Vec<Block> {
+    if items.is_empty() {
+        vec![Block::Paragraph(NONE_TEXT.to_string())]
+    } else {
+        vec![Block::List(items)]
+    }
+}
+
+// Period report
+
+#[derive(Debug, Serialize)]
+pub struct PeriodReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub total_count: usize,
+    pub completed_count: usize,
+    pub carried_over_count: usize,
+    pub daily_stats: Vec<DailyStat>,
+    pub completed_groups: Vec<ProjectGroup>,
+    pub milestones: Vec<Milestone>,
+    pub slipping: Vec<SlippingTodo>,
+    pub task_changes: Vec<TaskChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SlippingTodo {
+    pub title: String,
+    pub project_name: Option<String>,
+    pub last_date: String,
+    pub carry_count: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyStat {
+    pub date: String,
+    pub total: usize,
+    pub completed: usize,
+    // Todos on this day that were carried over from an earlier one
+    pub carried_over: usize,
+    pub completion_rate: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectGroup {
+    pub project_name: Option<String>,
+    pub tasks: Vec<TaskGroup>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskGroup {
+    pub task_id: Option<String>,
+    pub task_title: Option<String>,
+    pub items: Vec<ReportItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportItem {
+    pub date: String,
+    pub title: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskChange {
+    pub task_id: String,
+    pub task_title: String,
+    pub project_name: String,
+    pub status_from: Option<String>,
+    pub status_to: Option<String>,
+    pub progress_from: Option<String>,
+    pub progress_to: Option<String>,
+}
+
+pub fn build_period_report(
+    start: NaiveDate,
+    end: NaiveDate,
+    todos: &[DailyTodoWithTask],
+    activity: &[TaskActivity],
+    milestones: Vec<Milestone>,
+) -> PeriodReport {
+    let mut daily_stats = Vec::new();
+    for date in start.iter_days().take_while(|d| *d <= end) {
+        let date = date.format("%Y-%m-%d").to_string();
+        let day: Vec<_> = todos.iter().filter(|t| t.date == date).collect();
+        let completed = day.iter().filter(|t| t.completed).count();
+        daily_stats.push(DailyStat {
+            total: day.len(),
+            completed,
+            carried_over: day.iter().filter(|t| t.carry_count > 0).count(),
+            completion_rate: if day.is_empty() {
+                None
+            } else {
+                Some(completed as f64 / day.len() as f64)
+            },
+            date,
+        });
+    }
+
+    // Group completed todos by project, then by linked task, keeping first-seen order
+    let mut completed_groups: Vec<ProjectGroup> = Vec::new();
+    for todo in todos.iter().filter(|t| t.completed) {
+        let project_idx = match completed_groups.iter().position(|g| g.project_name == todo.project_name) {
+            Some(idx) => idx,
+            None => {
+                completed_groups.push(ProjectGroup {
+                    project_name: todo.project_name.clone(),
+                    tasks: Vec::new(),
+                });
+                completed_groups.len() - 1
+            }
+        };
+        let tasks = &mut completed_groups[project_idx].tasks;
+        let task_idx = match tasks.iter().position(|g| g.task_id == todo.task_id) {
+            Some(idx) => idx,
+            None => {
+                tasks.push(TaskGroup {
+                    task_id: todo.task_id.clone(),
+                    task_title: todo.task_title.clone(),
+                    items: Vec::new(),
+                });
+                tasks.len() - 1
+            }
+        };
+        tasks[task_idx].items.push(ReportItem {
+            date: todo.date.clone(),
+            title: todo.title.clone(),
+            memo: todo.memo.clone().filter(|m| !m.is_empty()),
+        });
+    }
+
+    // Chronically slipping todos, one entry per task/title with the highest carry count
+    let mut slipping: Vec<SlippingTodo> = Vec::new();
+    for todo in todos.iter().filter(|t| t.carry_count >= CHRONIC_CARRY_COUNT) {
+        match slipping.iter_mut().find(|s| s.title == todo.title && s.project_name == todo.project_name) {
+            Some(entry) if entry.carry_count < todo.carry_count => {
+                entry.carry_count = todo.carry_count;
+                entry.last_date = todo.date.clone();
+            }
+            Some(_) => {}
+            None => slipping.push(SlippingTodo {
+                title: todo.title.clone(),
+                project_name: todo.project_name.clone(),
+                last_date: todo.date.clone(),
+                carry_count: todo.carry_count,
+            }),
+        }
+    }
+
+    // Collapse the activity log into one first-to-last change per task
+    let mut task_changes: Vec<TaskChange> = Vec::new();
+    for entry in activity {
+        let idx = match task_changes.iter().position(|c| c.task_id == entry.task_id) {
+            Some(idx) => idx,
+            None => {
+                task_changes.push(TaskChange {
+                    task_id: entry.task_id.clone(),
+                    task_title: entry.task_title.clone(),
+                    project_name: entry.project_name.clone(),
+                    status_from: None,
+                    status_to: None,
+                    progress_from: None,
+                    progress_to: None,
+                });
+                task_changes.len() - 1
+            }
+        };
+        let change = &mut task_changes[idx];
+        let (from, to) = match entry.field.as_str() {
+            "status" => (&mut change.status_from, &mut change.status_to),
+            "progress" => (&mut change.progress_from, &mut change.progress_to),
+            _ => continue,
+        };
+        if to.is_none() {
+            *from = entry.old_value.clone();
+        }
+        *to = entry.new_value.clone();
+    }
+    task_changes.retain(|c| c.status_from != c.status_to || c.progress_from != c.progress_to);
+
+    let completed_count = todos.iter().filter(|t| t.completed).count();
+    let carried_over_count = todos.iter().filter(|t| t.carry_count > 0).count();
+    PeriodReport {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        total_count: todos.len(),
+        completed_count,
+        carried_over_count,
+        daily_stats,
+        completed_groups,
+        milestones,
+        slipping,
+        task_changes,
+    }
+}
+
+fn format_rate(rate: Option<f64>) -> String {
+    match rate {
+        Some(r) => format!("{:.0}%", r * 100.0),
+        None => "-".to_string(),
+    }
+}
+
+impl PeriodReport {
+    pub fn to_document(&self) -> Document {
+        let rate = if self.total_count == 0 {
+            None
+        } else {
+            Some(self.completed_count as f64 / self.total_count as f64)
+        };
+        let summary = vec![
+            ListItem::new(format!("TODO総数: {}", self.total_count), None, 0),
+            ListItem::new(format!("完了: {}", self.completed_count), None, 0),
+            ListItem::new(format!("持ち越し: {}", self.carried_over_count), None, 0),
+            ListItem::new(format!("完了率: {}", format_rate(rate)), None, 0),
+        ];
+
+        let mut completed = Vec::new();
+        for group in &self.completed_groups {
+            completed.push(Block::Subheading(
+                group.project_name.as_deref().unwrap_or(NO_PROJECT).to_string(),
+            ));
+            let mut items = Vec::new();
+            for task in &group.tasks {
+                items.push(ListItem::new(task.task_title.as_deref().unwrap_or(NO_TASK).to_string(), None, 0));
+                for item in &task.items {
+                    items.push(ListItem::new(format!("{} ({})", item.title, item.date), Some(true), 1));
+                    if let Some(ref m) = item.memo {
+                        items.push(ListItem::new(m.clone(), None, 2));
+                    }
+                }
+            }
+            completed.push(Block::List(items));
+        }
+        if completed.is_empty() {
+            completed.push(Block::Paragraph(NONE_TEXT.to_string()));
+        }
+
+        let daily = Block::Table {
+            headers: ["日付", "完了", "持ち越し", "完了率"].iter().map(|h| h.to_string()).collect(),
+            rows: self
+                .daily_stats
+                .iter()
+                .map(|stat| {
+                    vec![
+                        stat.date.clone(),
+                        format!("{}/{}", stat.completed, stat.total),
+                        stat.carried_over.to_string(),
+                        format_rate(stat.completion_rate),
+                    ]
+                })
+                .collect(),
+        };
+
+        let milestones = self
+            .milestones
+            .iter()
+            .map(|m| {
+                ListItem::new(
+                    format!("{} {}: {} ({})", m.date, m.project_name, m.title, status_label(m.status)),
+                    Some(m.status == TaskStatus::Completed),
+                    0,
+                )
+            })
+            .collect();
+
+        let slipping = self
+            .slipping
+            .iter()
+            .map(|todo| {
+                let title = match todo.project_name {
+                    Some(ref project) => format!("{}: {}", project, todo.title),
+                    None => todo.title.clone(),
+                };
+                ListItem::new(format!("{} ({}回, 最終 {})", title, todo.carry_count, todo.last_date), None, 0)
+            })
+            .collect();
+
+        let changes = self
+            .task_changes
+            .iter()
+            .map(|change| {
+                let mut parts = Vec::new();
+                if change.status_from != change.status_to {
+                    parts.push(format!(
+                        "ステータス {} → {}",
+                        change.status_from.as_deref().unwrap_or("-"),
+                        change.status_to.as_deref().unwrap_or("-")
+                    ));
+                }
+                if change.progress_from != change.progress_to {
+                    parts.push(format!(
+                        "進捗 {}% → {}%",
+                        change.progress_from.as_deref().unwrap_or("-"),
+                        change.progress_to.as_deref().unwrap_or("-")
+                    ));
+                }
+                ListItem::new(
+                    format!("{}: {} ({})", change.project_name, change.task_title, parts.join(", ")),
+                    None,
+                    0,
+                )
+            })
+            .collect();
+
+        Document {
+            title: format!("期間レポート - {} 〜 {}", self.start_date, self.end_date),
+            sections: vec![
+                Section { key: "summary", heading: None, blocks: vec![Block::List(summary)] },
+                Section { key: "completed", heading: Some("完了したタスク".to_string()), blocks: completed },
+                Section { key: "daily", heading: Some("日別の完了率".to_string()), blocks: vec![daily] },
+                Section { key: "milestones", heading: Some("マイルストーン".to_string()), blocks: list_or_none(milestones) },
+                Section { key: "slipping", heading: Some("繰り返し持ち越されたTODO".to_string()), blocks: list_or_none(slipping) },
+                Section { key: "changes", heading: Some("WBSタスクの変更".to_string()), blocks: list_or_none(changes) },
+            ],
+        }
+    }
+
+    // Templates only apply to Markdown output; other formats use the fixed layout
+    pub fn render(&self, format: ReportFormat, template: Option<&str>) -> String {
+        match format {
+            ReportFormat::Markdown => {
+                let doc = self.to_document();
+                fill_template(template.unwrap_or(DEFAULT_PERIOD_TEMPLATE), |key| match key {
+                    "start" => Some(self.start_date.clone()),
+                    "end" => Some(self.end_date.clone()),
+                    _ => doc
+                        .sections
+                        .iter()
+                        .find(|section| section.key == key)
+                        .map(|section| MarkdownRenderer.render_blocks(&section.blocks)),
+                })
+            }
+            ReportFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            other => renderer_for(other)
+                .map(|renderer| renderer.render(&self.to_document()))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+// Replaces each `{{key}}` in one pass, so placeholders inside substituted text stay literal;
+// unknown keys are kept as written
+fn fill_template(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let key = &after[..end];
+                match value(key) {
+                    Some(text) => out.push_str(&text),
+                    None => out.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Renderers
+
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn render_blocks(&self, blocks: &[Block]) -> String {
+        let mut out = String::new();
+        for block in blocks {
+            match block {
+                Block::Paragraph(text) => out.push_str(&format!("{}\n", text)),
+                Block::Subheading(text) => out.push_str(&format!("### {}\n", text)),
+                Block::List(items) => {
+                    for item in items {
+                        let check = match item.checked {
+                            Some(true) => "[x] ",
+                            Some(false) => "[ ] ",
+                            None => "",
+                        };
+                        out.push_str(&format!("{}- {}{}\n", "  ".repeat(item.depth), check, item.text));
+                    }
+                }
+                Block::Table { headers, rows } => {
+                    out.push_str(&format!("| {} |\n", headers.join(" | ")));
+                    out.push_str(&format!("|{}|\n", vec!["------"; headers.len()].join("|")));
+                    for row in rows {
+                        out.push_str(&format!("| {} |\n", row.join(" | ")));
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn render(&self, doc: &Document) -> String {
+        let sections: Vec<String> = doc
+            .sections
+            .iter()
+            .map(|section| match section.heading {
+                Some(ref heading) => format!("## {}\n{}", heading, self.render_blocks(&section.blocks)),
+                None => self.render_blocks(&section.blocks),
+            })
+            .collect();
+        format!("# {}\n\n{}", doc.title, sections.join("\n"))
+    }
+}
+
+pub struct HtmlRenderer;
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,'Hiragino Sans','Yu Gothic',sans-serif;max-width:800px;margin:2rem auto;padding:0 1rem;color:#1f2937;line-height:1.6}\
+h1{border-bottom:2px solid #e5e7eb;padding-bottom:.5rem}\
+h2{margin-top:2rem;color:#374151}\
+ul{padding-left:1.5rem}\
+li.done{color:#6b7280}\
+table{border-collapse:collapse}\
+th,td{border:1px solid #e5e7eb;padding:.25rem .75rem;text-align:left}\
+th{background:#f9fafb}";
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+impl Renderer for HtmlRenderer {
+    fn render_blocks(&self, blocks: &[Block]) -> String {
+        let mut out = String::new();
+        for block in blocks {
+            match block {
+                Block::Paragraph(text) => {
+                    out.push_str(&format!("<p>{}</p>\n", escape_html(text).replace('\n', "<br>")));
+                }
+                Block::Subheading(text) => out.push_str(&format!("<h3>{}</h3>\n", escape_html(text))),
+                Block::List(items) => {
+                    // Items carry a flat depth, so open/close nested lists as the depth changes
+                    let mut depth = 0;
+                    out.push_str("<ul>\n");
+                    for (i, item) in items.iter().enumerate() {
+                        if item.depth > depth {
+                            // Skipped levels get an empty item to hold their list
+                            if i > 0 {
+                                out.push_str("<ul>\n");
+                            }
+                            let skipped = item.depth - depth - usize::from(i > 0);
+                            out.push_str(&"<li><ul>\n".repeat(skipped));
+                        } else if i > 0 {
+                            out.push_str("</li>\n");
+                            out.push_str(&"</ul>\n</li>\n".repeat(depth - item.depth));
+                        }
+                        depth = item.depth;
+                        let (class, check) = match item.checked {
+                            Some(true) => (" class=\"done\"", "<input type=\"checkbox\" checked disabled> "),
+                            Some(false) => ("", "<input type=\"checkbox\" disabled> "),
+                            None => ("", ""),
+                        };
+                        out.push_str(&format!("<li{}>{}{}", class, check, escape_html(&item.text)));
+                    }
+                    out.push_str("</li>\n");
+                    out.push_str(&"</ul>\n</li>\n".repeat(depth));
+                    out.push_str("</ul>\n");
+                }
+                Block::Table { headers, rows } => {
+                    out.push_str("<table>\n<tr>");
+                    for header in headers {
+                        out.push_str(&format!("<th>{}</th>", escape_html(header)));
+                    }
+                    out.push_str("</tr>\n");
+                    for row in rows {
+                        out.push_str("<tr>");
+                        for cell in row {
+                            out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table>\n");
+                }
+            }
+        }
+        out
+    }
+
+    fn render(&self, doc: &Document) -> String {
+        let mut body = format!("<h1>{}</h1>\n", escape_html(&doc.title));
+        for section in &doc.sections {
+            if let Some(ref heading) = section.heading {
+                body.push_str(&format!("<h2>{}</h2>\n", escape_html(heading)));
+            }
+            body.push_str(&self.render_blocks(&section.blocks));
+        }
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"ja\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            escape_html(&doc.title),
+            HTML_STYLE,
+            body
+        )
+    }
+}
+
+// Plain text that reads well when pasted into Slack
+pub struct TextRenderer;
+
+impl Renderer for TextRenderer {
+    fn render_blocks(&self, blocks: &[Block]) -> String {
+        let mut out = String::new();
+        for block in blocks {
+            match block {
+                Block::Paragraph(text) => out.push_str(&format!("{}\n", text)),
+                Block::Subheading(text) => out.push_str(&format!("_{}_\n", text)),
+                Block::List(items) => {
+                    for item in items {
+                        let bullet = match item.checked {
+                            Some(true) => "✅",
+                            Some(false) => "⬜",
+                            None => "•",
+                        };
+                        out.push_str(&format!("{}{} {}\n", "    ".repeat(item.depth), bullet, item.text));
+                    }
+                }
+                Block::Table { headers, rows } => {
+                    out.push_str("```\n");
+                    out.push_str(&format!("{}\n", headers.join(" | ")));
+                    for row in rows {
+                        out.push_str(&format!("{}\n", row.join(" | ")));
+                    }
+                    out.push_str("```\n");
+                }
+            }
+        }
+        out
+    }
+
+    fn render(&self, doc: &Document) -> String {
+        let sections: Vec<String> = doc
+            .sections
+            .iter()
+            .map(|section| match section.heading {
+                Some(ref heading) => format!("*{}*\n{}", heading, self.render_blocks(&section.blocks)),
+                None => self.render_blocks(&section.blocks),
+            })
+            .collect();
+        format!("*{}*\n\n{}", doc.title, sections.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str, checked: Option<bool>, depth: usize) -> ListItem {
+        ListItem::new(text.to_string(), checked, depth)
+    }
+
+    fn document() -> Document {
+        Document {
+            title: "Report <1>".to_string(),
+            sections: vec![
+                Section {
+                    key: "summary",
+                    heading: None,
+                    blocks: vec![Block::Paragraph("Done & dusted".to_string())],
+                },
+                Section {
+                    key: "todos",
+                    heading: Some("TODO".to_string()),
+                    blocks: vec![
+                        Block::Subheading("Project".to_string()),
+                        Block::List(vec![item("done", Some(true), 0), item("open", Some(false), 1)]),
+                        Block::Table {
+                            headers: vec!["date".to_string(), "rate".to_string()],
+                            rows: vec![vec!["2024-06-10".to_string(), "50%".to_string()]],
+                        },
+                    ],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn markdown_renders_headings_checklists_and_tables() {
+        assert_eq!(
+            MarkdownRenderer.render(&document()),
+            "# Report <1>\n\nDone & dusted\n\n## TODO\n### Project\n- [x] done\n  - [ ] open\n\
+             | date | rate |\n|------|------|\n| 2024-06-10 | 50% |\n"
+        );
+    }
+
+    #[test]
+    fn text_renders_slack_friendly_markup() {
+        assert_eq!(
+            TextRenderer.render(&document()),
+            "*Report <1>*\n\nDone & dusted\n\n*TODO*\n_Project_\n✅ done\n    ⬜ open\n```\ndate | rate\n2024-06-10 | 50%\n```\n"
+        );
+    }
+
+    #[test]
+    fn html_escapes_text() {
+        assert_eq!(escape_html(r#"<a href="x">'&'</a>"#), "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;");
+        let html = HtmlRenderer.render(&document());
+        assert!(html.contains("<title>Report &lt;1&gt;</title>"));
+        assert!(html.contains("<p>Done &amp; dusted</p>"));
+        assert!(html.contains("<li class=\"done\"><input type=\"checkbox\" checked disabled> done"));
+        assert!(html.contains("<tr><td>2024-06-10</td><td>50%</td></tr>"));
+    }
+
+    #[test]
+    fn html_nests_lists_by_depth() {
+        let blocks = [Block::List(vec![
+            item("a", None, 0),
+            item("b", None, 1),
+            item("c", None, 1),
+            item("d", None, 0),
+        ])];
+        assert_eq!(
+            HtmlRenderer.render_blocks(&blocks),
+            "<ul>\n<li>a<ul>\n<li>b</li>\n<li>c</li>\n</ul>\n</li>\n<li>d</li>\n</ul>\n"
+        );
+        // Skipped levels are wrapped in items so every list sits inside an <li>
+        let blocks = [Block::List(vec![item("a", None, 0), item("b", None, 2)])];
+        assert_eq!(
+            HtmlRenderer.render_blocks(&blocks),
+            "<ul>\n<li>a<ul>\n<li><ul>\n<li>b</li>\n</ul>\n</li>\n</ul>\n</li>\n</ul>\n"
+        );
+        let blocks = [Block::List(vec![item("a", None, 1), item("b", None, 0)])];
+        assert_eq!(
+            HtmlRenderer.render_blocks(&blocks),
+            "<ul>\n<li><ul>\n<li>a</li>\n</ul>\n</li>\n<li>b</li>\n</ul>\n"
+        );
+    }
+
+    #[test]
+    fn json_has_no_renderer() {
+        assert!(renderer_for(ReportFormat::Json).is_none());
+        assert!(renderer_for(ReportFormat::Text).is_some());
+    }
+
+    #[test]
+    fn templates_are_filled_in_one_pass() {
+        let filled = fill_template("{{a}} {{b}} {{unknown}} {{open", |key| match key {
+            "a" => Some("{{b}}".to_string()),
+            "b" => Some("B".to_string()),
+            _ => None,
+        });
+        assert_eq!(filled, "{{b}} B {{unknown}} {{open");
+    }
+
+    fn todo(title: &str, date: &str, completed: bool, carry_count: i32) -> DailyTodoWithTask {
+        DailyTodoWithTask {
+            id: title.to_string(),
+            task_id: None,
+            title: title.to_string(),
+            date: date.to_string(),
+            completed,
+            memo: None,
+            carry_count,
+            carried_from: None,
+            order_index: 0,
+            priority: 0,
+            scheduled_time: None,
+            duration_minutes: None,
+            is_final: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+            task_title: None,
+            task_status: None,
+            task_progress: None,
+            parent_task_id: None,
+            parent_task_title: None,
+            project_name: None,
+            tracked_seconds: 0,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn period_reports_count_carried_over_todos_not_incomplete_ones() {
+        let start = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let todos = [
+            todo("new and open", "2024-06-10", false, 0),
+            todo("carried and done", "2024-06-11", true, 1),
+            todo("carried and open", "2024-06-11", false, 2),
+        ];
+
+        let report = build_period_report(start, start.succ_opt().unwrap(), &todos, &[], Vec::new());
+
+        assert_eq!(report.carried_over_count, 2);
+        let per_day: Vec<(usize, usize, usize)> = report.daily_stats.iter().map(|d| (d.total, d.completed, d.carried_over)).collect();
+        assert_eq!(per_day, [(1, 0, 0), (2, 1, 2)]);
+    }
+
+    fn linked_todo(title: &str, project: &str, parent: Option<(&str, &str)>, status: TaskStatus, progress: i32) -> DailyTodoWithTask {
+        DailyTodoWithTask {
+            task_status: Some(status),
+            task_progress: Some(progress),
+            parent_task_id: parent.map(|(id, _)| id.to_string()),
+            parent_task_title: parent.map(|(_, title)| title.to_string()),
+            project_name: Some(project.to_string()),
+            ..todo(title, "2024-06-10", true, 0)
+        }
+    }
+
+    #[test]
+    fn project_grouping_nests_todos_under_their_parent_task() {
+        let todos = vec![
+            linked_todo("画面設計", "Alpha", Some(("p1", "設計")), TaskStatus::InProgress, 50),
+            todo("雑務", "2024-06-10", true, 0),
+            linked_todo("API設計", "Alpha", Some(("p1", "設計")), TaskStatus::Completed, 100),
+            linked_todo("定例", "Alpha", None, TaskStatus::Pending, 0),
+        ];
+
+        let report = build_daily_report("2024-06-10", todos, "", ReportGrouping::Project);
+
+        assert_eq!(
+            report.render(ReportFormat::Markdown),
+            "# 日報 - 2024-06-10\n\n## 完了したタスク\n### Alpha\n- 設計\n  - [x] 画面設計 [進行中 50%]\n  - [x] API設計 [完了 100%]\n\
+             - [x] 定例 [未着手 0%]\n### プロジェクトなし\n- [x] 雑務\n\n## 未完了のタスク\nなし\n"
+        );
+    }
+
+    #[test]
+    fn tag_grouping_lists_a_todo_under_each_of_its_tags() {
+        let tagged = |title: &str, tags: &[&str]| DailyTodoWithTask {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..todo(title, "2024-06-10", false, 0)
+        };
+        let todos = vec![tagged("untagged", &[]), tagged("both", &["urgent", "client"]), tagged("client only", &["client"])];
+
+        let report = build_daily_report("2024-06-10", todos, "", ReportGrouping::Tag);
+
+        assert_eq!(
+            report.render(ReportFormat::Markdown),
+            "# 日報 - 2024-06-10\n\n## 完了したタスク\nなし\n\n## 未完了のタスク\n### urgent\n- [ ] both\n### client\n- [ ] both\n\
+             - [ ] client only\n### タグなし\n- [ ] untagged\n"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::db::{parse_date, Project, Task, TaskStatus};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+// Priority at or above which a task is suggested on its own
+pub const HIGH_PRIORITY: i32 = 2;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SuggestionReason {
+    // End date is before the target date
+    Overdue { days: i64 },
+    // Target date falls between start and end; `days_left` counts to the end date
+    Active { days_left: i64 },
+    // Task sets its project's finish date, so any slip moves the project
+    CriticalPath,
+    HighPriority,
+}
+
+impl SuggestionReason {
+    fn label(&self) -> String {
+        match self {
+            SuggestionReason::Overdue { days } => format!("{}日超過", days),
+            SuggestionReason::Active { days_left: 0 } => "本日期限".to_string(),
+            SuggestionReason::Active { days_left } => format!("期間中 (残り{}日)", days_left),
+            SuggestionReason::CriticalPath => "クリティカルパス".to_string(),
+            SuggestionReason::HighPriority => "優先度: 高".to_string(),
+        }
+    }
+
+    fn score(&self) -> i64 {
+        match self {
+            SuggestionReason::Overdue { days } => 100 + (*days).min(30) * 2,
+            SuggestionReason::Active { days_left } => 40 + (10 - days_left).max(0) * 2,
+            SuggestionReason::CriticalPath => 60,
+            SuggestionReason::HighPriority => 30,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TodoSuggestion {
+    pub task_id: String,
+    pub title: String,
+    pub project_id: String,
+    pub project_name: String,
+    pub status: TaskStatus,
+    pub priority: i32,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub score: i64,
+    pub reasons: Vec<SuggestionReason>,
+    // Reasons joined for display, e.g. "2日超過・優先度: 高"
+    pub reason: String,
+}
+
+// Without dependencies between tasks, the critical path is approximated by the open tasks
+// that finish last in their project or at/after the project's own end date
+fn critical_task_ids(tasks: &[Task], projects: &HashMap<&str, &Project>) -> HashSet<String> {
+    let mut latest: HashMap<&str, NaiveDate> = HashMap::new();
+    for task in tasks {
+        if let Some(end) = parse_date(&task.end_date) {
+            let entry = latest.entry(task.project_id.as_str()).or_insert(end);
+            *entry = (*entry).max(end);
+        }
+    }
+
+    tasks
+        .iter()
+        .filter(|task| {
+            let end = match parse_date(&task.end_date) {
+                Some(end) => end,
+                None => return false,
+            };
+            let project_end = projects.get(task.project_id.as_str()).and_then(|p| parse_date(&p.end_date));
+            latest.get(task.project_id.as_str()) == Some(&end) || project_end.is_some_and(|pe| end >= pe)
+        })
+        .map(|task| task.id.clone())
+        .collect()
+}
+
+// Ranks open leaf tasks as todo candidates for `date`; `planned` holds tasks already on that day
+pub fn suggest_todos(
+    date: NaiveDate,
+    tasks: &[Task],
+    projects: &[Project],
+    planned: &HashSet<String>,
+) -> Vec<TodoSuggestion> {
+    let projects: HashMap<&str, &Project> = projects.iter().map(|p| (p.id.as_str(), p)).collect();
+    let critical = critical_task_ids(tasks, &projects);
+
+    let mut suggestions: Vec<TodoSuggestion> = tasks
+        .iter()
+        .filter(|task| !planned.contains(&task.id))
+        .filter_map(|task| {
+            let start = parse_date(&task.start_date);
+            let end = parse_date(&task.end_date);
+
+            let mut reasons = Vec::new();
+            match end {
+                Some(end) if end < date => reasons.push(SuggestionReason::Overdue { days: (date - end).num_days() }),
+                Some(end) if start.is_none_or(|s| s <= date) => {
+                    reasons.push(SuggestionReason::Active { days_left: (end - date).num_days() })
+                }
+                _ => {}
+            }
+            // Only worth doing today once the task has started
+            if critical.contains(&task.id) && start.is_none_or(|s| s <= date) {
+                reasons.push(SuggestionReason::CriticalPath);
+            }
+            if task.priority >= HIGH_PRIORITY {
+                reasons.push(SuggestionReason::HighPriority);
+            }
+            if reasons.is_empty() {
+                return None;
+            }
+
+            Some(TodoSuggestion {
+                task_id: task.id.clone(),
+                title: task.title.clone(),
+                project_id: task.project_id.clone(),
+                project_name: projects.get(task.project_id.as_str()).map(|p| p.name.clone()).unwrap_or_default(),
+                status: task.status,
+                priority: task.priority,
+                start_date: task.start_date.clone(),
+                end_date: task.end_date.clone(),
+                score: reasons.iter().map(|r| r.score()).sum::<i64>() + i64::from(task.priority) * 5,
+                reason: reasons.iter().map(|r| r.label()).collect::<Vec<_>>().join("・"),
+                reasons,
+            })
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| (a.end_date.is_none(), &a.end_date).cmp(&(b.end_date.is_none(), &b.end_date)))
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::TaskKind;
+
+    fn task(id: &str, priority: i32, dates: Option<(&str, &str)>) -> Task {
+        Task {
+            id: id.to_string(),
+            project_id: "p".to_string(),
+            parent_id: None,
+            title: id.to_string(),
+            description: None,
+            status: TaskStatus::Pending,
+            priority,
+            start_date: dates.map(|(start, _)| start.to_string()),
+            end_date: dates.map(|(_, end)| end.to_string()),
+            progress: 0,
+            order_index: 0,
+            estimate_hours: None,
+            kind: TaskKind::Task,
+            progress_from_checklist: false,
+            created_at: String::new(),
+            version: 1,
+            updated_at: String::new(),
+        }
+    }
+
+    fn project() -> Project {
+        Project {
+            id: "p".to_string(),
+            name: "Project".to_string(),
+            description: None,
+            start_date: Some("2024-06-01".to_string()),
+            end_date: Some("2024-06-30".to_string()),
+            calendar_id: None,
+            created_at: String::new(),
+            version: 1,
+            updated_at: String::new(),
+        }
+    }
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 10).unwrap()
+    }
+
+    #[test]
+    fn tasks_are_ranked_by_urgency() {
+        let tasks = vec![
+            task("active", 0, Some(("2024-06-03", "2024-06-12"))),
+            task("high", HIGH_PRIORITY, None),
+            task("overdue", 0, Some(("2024-06-03", "2024-06-07"))),
+            task("critical", 0, Some(("2024-06-05", "2024-06-30"))),
+            task("future", 0, Some(("2024-06-20", "2024-06-25"))),
+        ];
+
+        let suggestions = suggest_todos(date(), &tasks, &[project()], &HashSet::new());
+
+        let ids: Vec<&str> = suggestions.iter().map(|s| s.task_id.as_str()).collect();
+        assert_eq!(ids, ["overdue", "critical", "active", "high"]);
+        assert_eq!(suggestions[0].reasons, [SuggestionReason::Overdue { days: 3 }]);
+        assert_eq!(suggestions[0].reason, "3日超過");
+        assert_eq!(
+            suggestions[1].reasons,
+            [SuggestionReason::Active { days_left: 20 }, SuggestionReason::CriticalPath]
+        );
+        assert_eq!(suggestions[3].score, 30 + HIGH_PRIORITY as i64 * 5);
+        assert_eq!(suggestions[0].project_name, "Project");
+    }
+
+    #[test]
+    fn planned_tasks_are_left_out() {
+        let tasks = vec![task("overdue", 0, Some(("2024-06-03", "2024-06-07")))];
+        let planned = HashSet::from(["overdue".to_string()]);
+
+        assert!(suggest_todos(date(), &tasks, &[project()], &planned).is_empty());
+    }
+
+    #[test]
+    fn critical_tasks_wait_until_they_start() {
+        let tasks = vec![task("late", 0, Some(("2024-06-20", "2024-07-05")))];
+
+        assert!(suggest_todos(date(), &tasks, &[project()], &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn ties_fall_back_to_end_date_then_title() {
+        // Overdue scores stop growing after 30 days
+        let tasks = vec![
+            task("b", HIGH_PRIORITY, None),
+            task("a", HIGH_PRIORITY, None),
+            task("newer", 0, Some(("2024-04-01", "2024-05-01"))),
+            task("older", 0, Some(("2024-04-01", "2024-04-21"))),
+            task("future", 0, Some(("2024-06-20", "2024-06-30"))),
+        ];
+
+        let suggestions = suggest_todos(date(), &tasks, &[project()], &HashSet::new());
+
+        let ids: Vec<&str> = suggestions.iter().map(|s| s.task_id.as_str()).collect();
+        assert_eq!(ids, ["older", "newer", "a", "b"]);
+    }
+}
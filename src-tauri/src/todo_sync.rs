@@ -0,0 +1,92 @@
+use crate::db::TaskStatus;
+use serde::{Deserialize, Serialize};
+
+// Setting key holding the JSON-encoded TodoSyncRules
+pub const SYNC_RULES_SETTING: &str = "todo_sync_rules";
+
+// How working on a daily todo feeds back into its linked WBS task. Sync only moves a task
+// forward: unchecking a todo never reopens the task, since it may have been completed by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodoSyncRules {
+    // Completing a linked todo, or starting a timer on it or its task, moves a pending task to in_progress
+    pub start_on_first_todo: bool,
+    // Completing a todo marked final completes the task at 100%
+    pub complete_on_final: bool,
+}
+
+impl Default for TodoSyncRules {
+    fn default() -> Self {
+        TodoSyncRules {
+            start_on_first_todo: true,
+            complete_on_final: true,
+        }
+    }
+}
+
+impl TodoSyncRules {
+    // Status the task moves to after a linked todo was toggled, if it changes at all
+    pub fn task_status_after_toggle(&self, task_status: TaskStatus, completed: bool, is_final: bool) -> Option<TaskStatus> {
+        if !completed || matches!(task_status, TaskStatus::Completed | TaskStatus::Cancelled) {
+            return None;
+        }
+        if is_final && self.complete_on_final {
+            Some(TaskStatus::Completed)
+        } else if self.start_on_first_todo && task_status == TaskStatus::Pending {
+            Some(TaskStatus::InProgress)
+        } else {
+            None
+        }
+    }
+
+    // Status the task moves to when a timer is started on it or on a linked todo
+    pub fn task_status_on_start(&self, task_status: TaskStatus) -> Option<TaskStatus> {
+        (self.start_on_first_todo && task_status == TaskStatus::Pending).then_some(TaskStatus::InProgress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONLY_START: TodoSyncRules = TodoSyncRules { start_on_first_todo: true, complete_on_final: false };
+
+    #[test]
+    fn completing_a_todo_starts_a_pending_task() {
+        let rules = TodoSyncRules::default();
+        assert_eq!(rules.task_status_after_toggle(TaskStatus::Pending, true, false), Some(TaskStatus::InProgress));
+        assert_eq!(rules.task_status_after_toggle(TaskStatus::InProgress, true, false), None);
+    }
+
+    #[test]
+    fn completing_the_final_todo_completes_the_task() {
+        let rules = TodoSyncRules::default();
+        assert_eq!(rules.task_status_after_toggle(TaskStatus::Pending, true, true), Some(TaskStatus::Completed));
+        assert_eq!(rules.task_status_after_toggle(TaskStatus::InProgress, true, true), Some(TaskStatus::Completed));
+        assert_eq!(ONLY_START.task_status_after_toggle(TaskStatus::InProgress, true, true), None);
+    }
+
+    #[test]
+    fn unchecking_or_finished_tasks_never_change() {
+        let rules = TodoSyncRules::default();
+        assert_eq!(rules.task_status_after_toggle(TaskStatus::Completed, false, true), None);
+        assert_eq!(rules.task_status_after_toggle(TaskStatus::InProgress, false, false), None);
+        assert_eq!(rules.task_status_after_toggle(TaskStatus::Completed, true, true), None);
+        assert_eq!(rules.task_status_after_toggle(TaskStatus::Cancelled, true, true), None);
+    }
+
+    #[test]
+    fn disabled_rules_leave_the_task_alone() {
+        let rules = TodoSyncRules { start_on_first_todo: false, complete_on_final: false };
+        assert_eq!(rules.task_status_after_toggle(TaskStatus::Pending, true, true), None);
+        assert_eq!(rules.task_status_on_start(TaskStatus::Pending), None);
+        assert_eq!(ONLY_START.task_status_on_start(TaskStatus::Pending), Some(TaskStatus::InProgress));
+        assert_eq!(ONLY_START.task_status_on_start(TaskStatus::Completed), None);
+    }
+
+    #[test]
+    fn missing_settings_keys_use_the_defaults() {
+        let rules: TodoSyncRules = serde_json::from_str(r#"{"complete_on_final":false}"#).unwrap();
+        assert_eq!(rules, TodoSyncRules { start_on_first_todo: true, complete_on_final: false });
+    }
+}
@@ -0,0 +1,133 @@
+use crate::calendar::WorkCalendar;
+use crate::db::{parse_date, Assignee, TaskAssignment};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+// Total allocation above this percentage marks a day as over-allocated
+pub const FULL_ALLOCATION: i32 = 100;
+
+#[derive(Debug, Serialize)]
+pub struct DayLoad {
+    pub date: String,
+    pub allocation: i32,
+    pub task_ids: Vec<String>,
+    pub over_allocated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssigneeWorkload {
+    pub assignee_id: String,
+    pub name: String,
+    pub days: Vec<DayLoad>,
+    pub over_allocated_days: usize,
+}
+
+// Per-person, per-working-day load from the spans of their assigned tasks
+pub fn compute_workload(
+    start: NaiveDate,
+    end: NaiveDate,
+    assignees: &[Assignee],
+    assignments: &[TaskAssignment],
+    calendar: &WorkCalendar,
+) -> Vec<AssigneeWorkload> {
+    let days: Vec<NaiveDate> = start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .filter(|d| calendar.is_working_day(*d))
+        .collect();
+
+    assignees
+        .iter()
+        .map(|assignee| {
+            let spans: Vec<_> = assignments
+                .iter()
+                .filter(|a| a.assignee_id == assignee.id)
+                .filter_map(|a| {
+                    let (task_start, task_end) = match (parse_date(&a.start_date), parse_date(&a.end_date)) {
+                        (Some(s), Some(e)) => (s, e.max(s)),
+                        (Some(d), None) | (None, Some(d)) => (d, d),
+                        (None, None) => return None,
+                    };
+                    Some((a, task_start, task_end))
+                })
+                .collect();
+
+            let days: Vec<DayLoad> = days
+                .iter()
+                .map(|day| {
+                    let active: Vec<_> = spans
+                        .iter()
+                        .filter(|(_, s, e)| s <= day && day <= e)
+                        .collect();
+                    let allocation = active.iter().map(|(a, _, _)| a.allocation).sum();
+                    DayLoad {
+                        date: day.format("%Y-%m-%d").to_string(),
+                        allocation,
+                        task_ids: active.iter().map(|(a, _, _)| a.task_id.clone()).collect(),
+                        over_allocated: allocation > FULL_ALLOCATION,
+                    }
+                })
+                .collect();
+
+            AssigneeWorkload {
+                assignee_id: assignee.id.clone(),
+                name: assignee.name.clone(),
+                over_allocated_days: days.iter().filter(|d| d.over_allocated).count(),
+                days,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn assignee() -> Assignee {
+        Assignee { id: "a".to_string(), name: "Alice".to_string(), email: None, created_at: String::new() }
+    }
+
+    fn assignment(task_id: &str, allocation: i32, start: &str, end: &str) -> TaskAssignment {
+        TaskAssignment {
+            task_id: task_id.to_string(),
+            assignee_id: "a".to_string(),
+            assignee_name: "Alice".to_string(),
+            allocation,
+            task_title: task_id.to_string(),
+            start_date: Some(start.to_string()),
+            end_date: Some(end.to_string()),
+        }
+    }
+
+    #[test]
+    fn overlapping_assignments_above_full_allocation_are_flagged() {
+        let assignments = [
+            assignment("t1", 60, "2024-06-03", "2024-06-05"),
+            assignment("t2", 50, "2024-06-05", "2024-06-07"),
+        ];
+
+        let workload = compute_workload(date("2024-06-03"), date("2024-06-07"), &[assignee()], &assignments, &WorkCalendar::standard());
+
+        let days = &workload[0].days;
+        let loads: Vec<(i32, bool)> = days.iter().map(|d| (d.allocation, d.over_allocated)).collect();
+        assert_eq!(loads, [(60, false), (60, false), (110, true), (50, false), (50, false)]);
+        assert_eq!(days[2].task_ids, ["t1", "t2"]);
+        assert_eq!(workload[0].over_allocated_days, 1);
+    }
+
+    #[test]
+    fn weekends_and_holidays_are_left_out() {
+        // Marine Day falls on Monday 2024-07-15
+        let assignments = [assignment("t1", 100, "2024-07-12", "2024-07-16")];
+
+        let workload = compute_workload(date("2024-07-12"), date("2024-07-16"), &[assignee()], &assignments, &WorkCalendar::standard());
+
+        let dates: Vec<&str> = workload[0].days.iter().map(|d| d.date.as_str()).collect();
+        assert_eq!(dates, ["2024-07-12", "2024-07-16"]);
+        assert_eq!(workload[0].over_allocated_days, 0);
+    }
+}
@@ -1,12 +1,20 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Project, Task, DailyTodo } from '../types';
+import type { Project, ProjectPatch, Task, TaskPatch, TaskStatus, TaskKind, Milestone, DailyTodo, TodoSyncRules, TodoSuggestion, RecurrenceRule, RecurringTodo, TimeEntry, TimeTotal, ProjectEarnedValue, Assignee, TaskAssignment, AssigneeWorkload, Calendar, Holiday, Weekday, Baseline, BaselineTask, BaselineComparison, SearchFilters, SearchHit, TaskQuery, TaskPage, SavedView, Tag, TagLink, CustomField, FieldType, TaskFieldValue, TaskComment, TimelineEntry, Attachment, ChecklistItem, BulkItemResult, ChangeSet } from '../types';
 
 // Extended DailyTodo with task info
 export interface DailyTodoWithTask extends DailyTodo {
   task_title: string | null;
+  task_status: TaskStatus | null;
+  task_progress: number | null;
+  parent_task_id: string | null;
+  parent_task_title: string | null;
   project_name: string | null;
+  tracked_seconds: number;
+  tags: string[];
 }
 
+export type CarryOverMode = 'move' | 'copy';
+
 // Project API
 export const projectApi = {
   create: (data: { name: string; description?: string; start_date?: string; end_date?: string }) =>
@@ -30,6 +38,9 @@ export const projectApi = {
       endDate: data.end_date || null,
     }),
 
+  // Rejected with { code: 'conflict' } when the project changed since `version`
+  patch: (id: string, patch: ProjectPatch, version: number) => invoke<Project>('patch_project', { id, patch, version }),
+
   delete: (id: string) => invoke<void>('delete_project', { id }),
 };
 
@@ -44,6 +55,7 @@ export const taskApi = {
     priority: number;
     start_date?: string;
     end_date?: string;
+    kind?: TaskKind;
   }) =>
     invoke<Task>('create_task', {
       projectId: data.project_id,
@@ -54,10 +66,13 @@ export const taskApi = {
       priority: data.priority,
       startDate: data.start_date || null,
       endDate: data.end_date || null,
+      kind: data.kind || null,
     }),
 
   getByProject: (projectId: string) => invoke<Task[]>('get_tasks_by_project', { projectId }),
 
+  query: (query: TaskQuery) => invoke<TaskPage>('query_tasks', { query }),
+
   update: (
     id: string,
     data: {
@@ -81,8 +96,21 @@ export const taskApi = {
       progress: data.progress,
     }),
 
-  updateDates: (id: string, startDate: string | null, endDate: string | null) =>
-    invoke<void>('update_task_dates', { id, startDate, endDate }),
+  // Rejected with { code: 'conflict' } when the task changed since `version`
+  patch: (id: string, patch: TaskPatch, version: number) => invoke<Task>('patch_task', { id, patch, version }),
+
+  updateDates: (id: string, startDate: string | null, endDate: string | null, workingDays?: boolean) =>
+    invoke<Task>('update_task_dates', { id, startDate, endDate, workingDays: workingDays ?? null }),
+
+  updateEstimate: (id: string, estimateHours: number | null) =>
+    invoke<void>('update_task_estimate', { id, estimateHours }),
+
+  earnedValue: (projectId: string, asOf?: string) =>
+    invoke<ProjectEarnedValue>('project_earned_value', { projectId, asOf: asOf || null }),
+
+  setKind: (id: string, kind: TaskKind) => invoke<Task>('set_task_kind', { id, kind }),
+
+  upcomingMilestones: (days?: number) => invoke<Milestone[]>('get_upcoming_milestones', { days: days ?? null }),
 
   delete: (id: string) => invoke<void>('delete_task', { id }),
 };
@@ -103,13 +131,312 @@ export const todoApi = {
 
   updateMemo: (id: string, memo: string | null) => invoke<void>('update_todo_memo', { id, memo }),
 
+  setFinal: (id: string, isFinal: boolean) => invoke<void>('set_todo_final', { id, isFinal }),
+
+  getSyncRules: () => invoke<TodoSyncRules>('get_todo_sync_rules'),
+
+  setSyncRules: (rules: TodoSyncRules) => invoke<void>('set_todo_sync_rules', { rules }),
+
+  updateSchedule: (id: string, priority: number, scheduledTime?: string | null, durationMinutes?: number | null) =>
+    invoke<void>('update_todo_schedule', { id, priority, scheduledTime: scheduledTime || null, durationMinutes: durationMinutes ?? null }),
+
+  reorder: (date: string, ids: string[]) => invoke<void>('reorder_todos', { date, ids }),
+
   delete: (id: string) => invoke<void>('delete_todo', { id }),
 
   addFromTask: (taskId: string, date: string) => invoke<DailyTodo>('add_task_to_todo', { taskId, date }),
+
+  suggest: (date: string, limit?: number) => invoke<TodoSuggestion[]>('suggest_todos', { date, limit: limit ?? null }),
+
+  carryOver: (fromDate: string, toDate: string, mode?: CarryOverMode) =>
+    invoke<DailyTodo[]>('carry_over_todos', { fromDate, toDate, mode: mode || null }),
+};
+
+// Recurring TODO API
+export const recurringTodoApi = {
+  create: (data: { task_id?: string; title: string; memo?: string; rule: RecurrenceRule; start_date: string; end_date?: string }) =>
+    invoke<RecurringTodo>('create_recurring_todo', {
+      taskId: data.task_id || null,
+      title: data.title,
+      memo: data.memo || null,
+      rule: data.rule,
+      startDate: data.start_date,
+      endDate: data.end_date || null,
+    }),
+
+  getAll: () => invoke<RecurringTodo[]>('get_recurring_todos'),
+
+  update: (
+    id: string,
+    data: { task_id?: string; title: string; memo?: string; rule: RecurrenceRule; start_date: string; end_date?: string }
+  ) =>
+    invoke<void>('update_recurring_todo', {
+      id,
+      taskId: data.task_id || null,
+      title: data.title,
+      memo: data.memo || null,
+      rule: data.rule,
+      startDate: data.start_date,
+      endDate: data.end_date || null,
+    }),
+
+  delete: (id: string) => invoke<void>('delete_recurring_todo', { id }),
+};
+
+// Time tracking API
+export const timeApi = {
+  start: (data: { todo_id?: string; task_id?: string; note?: string }) =>
+    invoke<TimeEntry>('start_timer', {
+      todoId: data.todo_id || null,
+      taskId: data.task_id || null,
+      note: data.note || null,
+    }),
+
+  stop: () => invoke<TimeEntry | null>('stop_timer'),
+
+  getRunning: () => invoke<TimeEntry | null>('get_running_timer'),
+
+  // Times are ISO-8601 with an offset, e.g. new Date().toISOString()
+  add: (data: { todo_id?: string; task_id?: string; started_at: string; ended_at: string; note?: string }) =>
+    invoke<TimeEntry>('add_time_entry', {
+      todoId: data.todo_id || null,
+      taskId: data.task_id || null,
+      startedAt: data.started_at,
+      endedAt: data.ended_at,
+      note: data.note || null,
+    }),
+
+  getEntries: (filter: { todo_id?: string; task_id?: string }) =>
+    invoke<TimeEntry[]>('get_time_entries', { todoId: filter.todo_id || null, taskId: filter.task_id || null }),
+
+  delete: (id: string) => invoke<void>('delete_time_entry', { id }),
+
+  byTask: (startDate: string, endDate: string) => invoke<TimeTotal[]>('get_time_by_task', { startDate, endDate }),
+
+  byProject: (startDate: string, endDate: string) => invoke<TimeTotal[]>('get_time_by_project', { startDate, endDate }),
+
+  byDay: (startDate: string, endDate: string) => invoke<TimeTotal[]>('get_time_by_day', { startDate, endDate }),
+};
+
+// Assignee API
+export const assigneeApi = {
+  create: (data: { name: string; email?: string }) =>
+    invoke<Assignee>('create_assignee', { name: data.name, email: data.email || null }),
+
+  getAll: () => invoke<Assignee[]>('get_all_assignees'),
+
+  update: (id: string, data: { name: string; email?: string }) =>
+    invoke<void>('update_assignee', { id, name: data.name, email: data.email || null }),
+
+  delete: (id: string) => invoke<void>('delete_assignee', { id }),
+
+  assign: (taskId: string, assigneeId: string, allocation?: number) =>
+    invoke<void>('assign_task', { taskId, assigneeId, allocation: allocation ?? null }),
+
+  unassign: (taskId: string, assigneeId: string) => invoke<void>('unassign_task', { taskId, assigneeId }),
+
+  getByTask: (taskId: string) => invoke<TaskAssignment[]>('get_task_assignments', { taskId }),
+
+  workload: (startDate: string, endDate: string) =>
+    invoke<AssigneeWorkload[]>('get_workload', { startDate, endDate }),
+};
+
+// Custom field API
+export type ExportFormat = 'csv' | 'json';
+
+export const customFieldApi = {
+  create: (projectId: string, name: string, fieldType: FieldType, options?: string[]) =>
+    invoke<CustomField>('create_custom_field', { projectId, name, fieldType, options: options || null }),
+
+  getByProject: (projectId: string) => invoke<CustomField[]>('get_custom_fields', { projectId }),
+
+  update: (id: string, name: string, options?: string[]) =>
+    invoke<void>('update_custom_field', { id, name, options: options || null }),
+
+  delete: (id: string) => invoke<void>('delete_custom_field', { id }),
+
+  setValue: (taskId: string, fieldId: string, value: string | null) =>
+    invoke<string | null>('set_task_field_value', { taskId, fieldId, value }),
+
+  getValues: (projectId: string) => invoke<TaskFieldValue[]>('get_task_field_values', { projectId }),
+
+  exportTasks: (projectId: string, format?: ExportFormat) =>
+    invoke<string>('export_tasks', { projectId, format: format || null }),
+};
+
+// Tag API
+export const tagApi = {
+  create: (name: string, color: string) => invoke<Tag>('create_tag', { name, color }),
+
+  getAll: () => invoke<Tag[]>('get_all_tags'),
+
+  update: (id: string, name: string, color: string) => invoke<void>('update_tag', { id, name, color }),
+
+  delete: (id: string) => invoke<void>('delete_tag', { id }),
+
+  tagTask: (taskId: string, tagId: string) => invoke<void>('tag_task', { taskId, tagId }),
+
+  untagTask: (taskId: string, tagId: string) => invoke<void>('untag_task', { taskId, tagId }),
+
+  tagTodo: (todoId: string, tagId: string) => invoke<void>('tag_todo', { todoId, tagId }),
+
+  untagTodo: (todoId: string, tagId: string) => invoke<void>('untag_todo', { todoId, tagId }),
+
+  getTaskLinks: (projectId: string) => invoke<TagLink[]>('get_task_tag_links', { projectId }),
+
+  getTodos: (tagId: string, startDate?: string, endDate?: string) =>
+    invoke<DailyTodoWithTask[]>('get_todos_by_tag', { tagId, startDate: startDate || null, endDate: endDate || null }),
+};
+
+// Saved view API
+export const savedViewApi = {
+  create: (name: string, query: TaskQuery) => invoke<SavedView>('create_saved_view', { name, query }),
+
+  getAll: () => invoke<SavedView[]>('get_saved_views'),
+
+  update: (id: string, name: string, query: TaskQuery) => invoke<void>('update_saved_view', { id, name, query }),
+
+  reorder: (ids: string[]) => invoke<void>('reorder_saved_views', { ids }),
+
+  delete: (id: string) => invoke<void>('delete_saved_view', { id }),
+
+  evaluate: (id: string, limit?: number, offset?: number) =>
+    invoke<TaskPage>('evaluate_saved_view', { id, limit: limit ?? null, offset: offset ?? null }),
+};
+
+// Comment API
+export const commentApi = {
+  add: (taskId: string, body: string, parentId?: string, author?: string) =>
+    invoke<TaskComment>('add_task_comment', { taskId, body, parentId: parentId || null, author: author || null }),
+
+  update: (id: string, body: string) => invoke<void>('update_task_comment', { id, body }),
+
+  delete: (id: string) => invoke<void>('delete_task_comment', { id }),
+
+  timeline: (taskId: string) => invoke<TimelineEntry[]>('get_task_timeline', { taskId }),
+};
+
+// Bulk API
+export const bulkApi = {
+  setTaskStatus: (ids: string[], status: TaskStatus) => invoke<BulkItemResult[]>('bulk_set_task_status', { ids, status }),
+
+  setTaskPriority: (ids: string[], priority: number) => invoke<BulkItemResult[]>('bulk_set_task_priority', { ids, priority }),
+
+  shiftTaskDates: (ids: string[], days: number, workingDays?: boolean) =>
+    invoke<BulkItemResult[]>('bulk_shift_task_dates', { ids, days, workingDays: workingDays ?? null }),
+
+  deleteTasks: (ids: string[]) => invoke<BulkItemResult[]>('bulk_delete_tasks', { ids }),
+
+  addTasksToTodos: (ids: string[], date: string) => invoke<BulkItemResult[]>('bulk_add_tasks_to_todos', { ids, date }),
+};
+
+// Checklist API
+export const checklistApi = {
+  add: (taskId: string, title: string) => invoke<ChecklistItem>('add_checklist_item', { taskId, title }),
+
+  getByTask: (taskId: string) => invoke<ChecklistItem[]>('get_checklist_items', { taskId }),
+
+  update: (id: string, title: string) => invoke<void>('update_checklist_item', { id, title }),
+
+  toggle: (id: string) => invoke<boolean>('toggle_checklist_item', { id }),
+
+  reorder: (taskId: string, ids: string[]) => invoke<void>('reorder_checklist_items', { taskId, ids }),
+
+  delete: (id: string) => invoke<void>('delete_checklist_item', { id }),
+
+  setProgressFromChecklist: (taskId: string, enabled: boolean) =>
+    invoke<void>('set_progress_from_checklist', { taskId, enabled }),
+};
+
+// Attachment API
+export const attachmentApi = {
+  attach: (taskId: string, path: string) => invoke<Attachment>('attach_file', { taskId, path }),
+
+  getByTask: (taskId: string) => invoke<Attachment[]>('get_attachments', { taskId }),
+
+  open: (id: string) => invoke<void>('open_attachment', { id }),
+
+  delete: (id: string) => invoke<void>('delete_attachment', { id }),
+
+  // Returns the path of the created backup folder
+  backup: (targetDir: string) => invoke<string>('backup_data', { targetDir }),
+};
+
+// Search API
+export const searchApi = {
+  search: (query: string, filters?: SearchFilters) => invoke<SearchHit[]>('search', { query, filters: filters || null }),
+};
+
+// Baseline API
+export const baselineApi = {
+  create: (projectId: string, name: string) => invoke<Baseline>('create_baseline', { projectId, name }),
+
+  getByProject: (projectId: string) => invoke<Baseline[]>('get_baselines', { projectId }),
+
+  rename: (id: string, name: string) => invoke<void>('rename_baseline', { id, name }),
+
+  delete: (id: string) => invoke<void>('delete_baseline', { id }),
+
+  getTasks: (baselineId: string) => invoke<BaselineTask[]>('get_baseline_tasks', { baselineId }),
+
+  compare: (projectId: string, baselineId: string) =>
+    invoke<BaselineComparison>('compare_baseline', { projectId, baselineId }),
+};
+
+// Calendar API
+export const calendarApi = {
+  create: (name: string, workWeek?: Weekday[], useJpHolidays?: boolean) =>
+    invoke<Calendar>('create_calendar', { name, workWeek: workWeek || null, useJpHolidays: useJpHolidays ?? null }),
+
+  getAll: () => invoke<Calendar[]>('get_calendars'),
+
+  update: (id: string, name: string, workWeek: Weekday[], useJpHolidays: boolean) =>
+    invoke<void>('update_calendar', { id, name, workWeek, useJpHolidays }),
+
+  delete: (id: string) => invoke<void>('delete_calendar', { id }),
+
+  setForProject: (projectId: string, calendarId: string | null) =>
+    invoke<void>('set_project_calendar', { projectId, calendarId }),
+
+  addHoliday: (calendarId: string, date: string, name: string) =>
+    invoke<void>('add_holiday', { calendarId, date, name }),
+
+  deleteHoliday: (calendarId: string, date: string) => invoke<void>('delete_holiday', { calendarId, date }),
+
+  getHolidays: (calendarId: string, year?: number) =>
+    invoke<Holiday[]>('get_holidays', { calendarId, year: year ?? null }),
+
+  importHolidays: (calendarId: string, path: string) => invoke<number>('import_holidays', { calendarId, path }),
+
+  countWorkingDays: (startDate: string, endDate: string, projectId?: string) =>
+    invoke<number>('count_working_days', { projectId: projectId || null, startDate, endDate }),
+
+  addWorkingDays: (date: string, days: number, projectId?: string) =>
+    invoke<string>('add_working_days', { projectId: projectId || null, date, days }),
+};
+
+// Change tracking API
+export const changesApi = {
+  // `since` is an ISO-8601 timestamp with offset, e.g. a previous ChangeSet's `until`
+  since: (since: string) => invoke<ChangeSet>('get_changes_since', { since }),
+};
+
+// Settings API
+export const settingsApi = {
+  get: (key: string) => invoke<string | null>('get_setting', { key }),
+
+  set: (key: string, value: string) => invoke<void>('set_setting', { key, value }),
 };
 
 // Report API
+export type ReportFormat = 'markdown' | 'html' | 'text' | 'json';
+export type ReportGrouping = 'flat' | 'project' | 'tag';
+
 export const reportApi = {
-  generate: (date: string, memo: string) => invoke<string>('generate_daily_report', { date, memo }),
+  generate: (date: string, memo: string, format?: ReportFormat, groupBy?: ReportGrouping) =>
+    invoke<string>('generate_daily_report', { date, memo, format: format || null, groupBy: groupBy || null }),
+
+  generatePeriod: (startDate: string, endDate: string, template?: string, format?: ReportFormat) =>
+    invoke<string>('generate_period_report', { startDate, endDate, template: template || null, format: format || null }),
 };
 
@@ -5,12 +5,27 @@ export interface Project {
   description: string | null;
   start_date: string | null;
   end_date: string | null;
+  calendar_id: string | null;
   created_at: string;
+  // Bumped on every write; patches must name the version they were based on
+  version: number;
+  // UTC ISO-8601, e.g. "2024-05-01T03:04:05.678Z"
+  updated_at: string;
+}
+
+// Fields to change in a patch; omitted keys keep their value, null clears it
+export interface ProjectPatch {
+  name?: string;
+  description?: string | null;
+  start_date?: string | null;
+  end_date?: string | null;
 }
 
 // Task status
 export type TaskStatus = 'pending' | 'in_progress' | 'completed' | 'cancelled';
 
+export type TaskKind = 'task' | 'milestone' | 'summary';
+
 // Task type (WBS item)
 export interface Task {
   id: string;
@@ -24,10 +39,26 @@ export interface Task {
   end_date: string | null;
   progress: number;
   order_index: number;
+  estimate_hours: number | null;
+  kind: TaskKind;
+  // Progress follows the checklist completion ratio
+  progress_from_checklist: boolean;
   created_at: string;
+  version: number;
+  updated_at: string;
   children?: Task[];
 }
 
+export interface TaskPatch {
+  title?: string;
+  description?: string | null;
+  status?: TaskStatus;
+  priority?: number;
+  start_date?: string | null;
+  end_date?: string | null;
+  progress?: number;
+}
+
 // Daily TODO type
 export interface DailyTodo {
   id: string;
@@ -36,11 +67,144 @@ export interface DailyTodo {
   date: string;
   completed: boolean;
   memo: string | null;
+  carry_count: number;
+  carried_from: string | null;
+  order_index: number;
+  priority: number;
+  // "HH:MM" start of the time box, with its length in minutes
+  scheduled_time: string | null;
+  duration_minutes: number | null;
+  // Completing this todo finishes the linked task
+  is_final: boolean;
   created_at: string;
+  updated_at: string;
   task?: Task;
   project?: Project;
 }
 
+// How completing a daily todo feeds back into its linked WBS task
+export interface TodoSyncRules {
+  start_on_first_todo: boolean;
+  complete_on_final: boolean;
+}
+
+export type SuggestionReason =
+  | { kind: 'overdue'; days: number }
+  | { kind: 'active'; days_left: number }
+  | { kind: 'critical_path' }
+  | { kind: 'high_priority' };
+
+// Candidate daily todo proposed from a WBS leaf task
+export interface TodoSuggestion {
+  task_id: string;
+  title: string;
+  project_id: string;
+  project_name: string;
+  status: TaskStatus;
+  priority: number;
+  start_date: string | null;
+  end_date: string | null;
+  score: number;
+  reasons: SuggestionReason[];
+  reason: string;
+}
+
+// Recurrence rule for recurring todos
+export type Weekday = 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+
+export type RecurrenceRule =
+  | { kind: 'weekdays' }
+  | { kind: 'weekly'; weekdays: Weekday[] }
+  | { kind: 'first_business_day_of_month' }
+  | { kind: 'every_n_days'; interval: number };
+
+// Recurring TODO definition
+export interface RecurringTodo {
+  id: string;
+  task_id: string | null;
+  title: string;
+  memo: string | null;
+  rule: RecurrenceRule;
+  start_date: string;
+  end_date: string | null;
+  created_at: string;
+}
+
+// Time tracking entry (ended_at is null while the timer is running); times are UTC ISO-8601
+export interface TimeEntry {
+  id: string;
+  todo_id: string | null;
+  task_id: string | null;
+  started_at: string;
+  ended_at: string | null;
+  duration_seconds: number | null;
+  note: string | null;
+  created_at: string;
+}
+
+// Tracked time aggregated per task, project or day
+export interface TimeTotal {
+  key: string | null;
+  label: string | null;
+  seconds: number;
+}
+
+// Earned value metrics (amounts in estimated hours)
+export interface EarnedValue {
+  bac: number;
+  pv: number;
+  ev: number;
+  ac: number;
+  spi: number | null;
+  cpi: number | null;
+  eac: number | null;
+}
+
+export interface TaskEarnedValue extends EarnedValue {
+  task_id: string;
+  parent_id: string | null;
+  title: string;
+}
+
+export interface ProjectEarnedValue extends EarnedValue {
+  project_id: string;
+  as_of: string;
+  tasks: TaskEarnedValue[];
+}
+
+// Person that tasks can be assigned to
+export interface Assignee {
+  id: string;
+  name: string;
+  email: string | null;
+  created_at: string;
+}
+
+// Task assignment with allocation percentage
+export interface TaskAssignment {
+  task_id: string;
+  assignee_id: string;
+  assignee_name: string;
+  allocation: number;
+  task_title: string;
+  start_date: string | null;
+  end_date: string | null;
+}
+
+export interface DayLoad {
+  date: string;
+  allocation: number;
+  task_ids: string[];
+  over_allocated: boolean;
+}
+
+export interface AssigneeWorkload {
+  assignee_id: string;
+  name: string;
+  days: DayLoad[];
+  over_allocated_days: number;
+}
+
 // View type
 export type ViewType = 'wbs' | 'gantt' | 'today' | 'calendar';
 
@@ -81,3 +245,234 @@ export interface DailyReport {
   memo: string;
 }
 
+
+// Calendar types
+export interface Calendar {
+  id: string;
+  name: string;
+  work_week: Weekday[];
+  use_jp_holidays: boolean;
+  created_at: string;
+}
+
+export interface Holiday {
+  date: string;
+  name: string;
+}
+
+// Baseline types
+export interface Baseline {
+  id: string;
+  project_id: string;
+  name: string;
+  created_at: string;
+}
+
+export interface BaselineTask {
+  baseline_id: string;
+  task_id: string;
+  title: string;
+  start_date: string | null;
+  end_date: string | null;
+  progress: number;
+}
+
+export type VarianceKind = 'planned' | 'added' | 'removed';
+
+export interface TaskVariance {
+  task_id: string;
+  title: string;
+  kind: VarianceKind;
+  baseline_start: string | null;
+  baseline_end: string | null;
+  current_start: string | null;
+  current_end: string | null;
+  start_slip_days: number | null;
+  finish_slip_days: number | null;
+  baseline_progress: number | null;
+  current_progress: number | null;
+  progress_variance: number | null;
+}
+
+export interface BaselineComparison {
+  baseline: Baseline;
+  project_finish_slip_days: number | null;
+  slipped_tasks: number;
+  tasks: TaskVariance[];
+}
+
+export interface Milestone {
+  id: string;
+  project_id: string;
+  project_name: string;
+  title: string;
+  date: string;
+  status: TaskStatus;
+  progress: number;
+}
+
+// Search types
+export type SearchKind = 'project' | 'task' | 'todo';
+
+export interface SearchFilters {
+  kinds?: SearchKind[];
+  project_id?: string;
+  date_from?: string;
+  date_to?: string;
+  limit?: number;
+}
+
+export interface SearchHit {
+  kind: SearchKind;
+  id: string;
+  title: string;
+  snippet: string;
+  rank: number;
+  project_id: string | null;
+  project_name: string | null;
+  task_id: string | null;
+  task_title: string | null;
+  date: string | null;
+}
+
+// Task query types
+export type TaskSort = 'order_index' | 'priority' | 'start_date' | 'end_date' | 'title' | 'created_at';
+
+export interface TaskQuery {
+  project_id?: string;
+  statuses?: TaskStatus[];
+  kinds?: TaskKind[];
+  priority_min?: number;
+  priority_max?: number;
+  window_start?: string;
+  window_end?: string;
+  overdue?: boolean;
+  due_within_days?: number;
+  assignee_id?: string;
+  tag_id?: string;
+  fields?: FieldFilter[];
+  text?: string;
+  sort?: TaskSort;
+  descending?: boolean;
+  limit?: number;
+  offset?: number;
+}
+
+export interface TaskPage {
+  tasks: Task[];
+  total: number;
+}
+
+export interface SavedView {
+  id: string;
+  name: string;
+  query: TaskQuery;
+  order_index: number;
+  created_at: string;
+}
+
+// Tag types
+export interface Tag {
+  id: string;
+  name: string;
+  color: string;
+  created_at: string;
+}
+
+export interface TagLink {
+  item_id: string;
+  tag_id: string;
+}
+
+// Custom field types
+export type FieldType = 'text' | 'number' | 'date' | 'enum' | 'checkbox';
+
+export interface CustomField {
+  id: string;
+  project_id: string;
+  name: string;
+  field_type: FieldType;
+  options: string[];
+  order_index: number;
+  created_at: string;
+}
+
+export interface TaskFieldValue {
+  task_id: string;
+  field_id: string;
+  value: string;
+}
+
+export type FieldOp = 'eq' | 'contains' | 'gte' | 'lte';
+
+export interface FieldFilter {
+  field_id: string;
+  op: FieldOp;
+  value: string;
+}
+
+// Comment and activity types
+export interface TaskComment {
+  id: string;
+  task_id: string;
+  parent_id: string | null;
+  author: string | null;
+  body: string;
+  created_at: string;
+  edited_at: string | null;
+}
+
+// Outcome for one id of a bulk command
+export interface BulkItemResult {
+  id: string;
+  ok: boolean;
+  error: string | null;
+}
+
+export interface DeletedRecord {
+  kind: 'project' | 'task' | 'todo';
+  id: string;
+  deleted_at: string;
+}
+
+// Rows written since a point in time; pass `until` as the next `since`
+export interface ChangeSet {
+  since: string;
+  until: string;
+  projects: Project[];
+  tasks: Task[];
+  todos: DailyTodo[];
+  deleted: DeletedRecord[];
+}
+
+export interface ChecklistItem {
+  id: string;
+  task_id: string;
+  title: string;
+  checked: boolean;
+  order_index: number;
+  created_at: string;
+}
+
+export interface Attachment {
+  id: string;
+  task_id: string;
+  name: string;
+  size: number;
+  mime: string;
+  hash: string;
+  created_at: string;
+}
+
+export interface TaskActivity {
+  id: string;
+  task_id: string;
+  field: 'title' | 'status' | 'progress' | 'start_date' | 'end_date';
+  old_value: string | null;
+  new_value: string | null;
+  changed_at: string;
+  task_title: string;
+  project_name: string;
+}
+
+export type TimelineEntry = ({ type: 'comment' } & TaskComment) | ({ type: 'activity' } & TaskActivity);